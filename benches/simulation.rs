@@ -0,0 +1,82 @@
+use bumpalo::Bump;
+use core_of_color::state::bench;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+const SEED: u64 = 42;
+const DOTS: [usize; 3] = [1_000, 10_000, 50_000];
+
+fn partition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("partition");
+    for dots in DOTS {
+        let state = bench::state(SEED, dots);
+        let mut bump = Bump::new();
+        group.bench_function(BenchmarkId::from_parameter(dots), |b| {
+            b.iter(|| bench::partition(&state, &mut bump))
+        });
+    }
+    group.finish();
+}
+
+fn neighbours(c: &mut Criterion) {
+    let mut group = c.benchmark_group("neighbours");
+    group.sample_size(10);
+    for dots in DOTS {
+        let state = bench::state(SEED, dots);
+        let bump = Bump::new();
+        let neighbours = bench::Neighbours::new(&state, &bump);
+        group.bench_function(BenchmarkId::from_parameter(dots), |b| {
+            b.iter(|| neighbours.run())
+        });
+    }
+    group.finish();
+}
+
+fn attract(c: &mut Criterion) {
+    let mut group = c.benchmark_group("attract");
+    group.sample_size(10);
+    for dots in DOTS {
+        let mut state = bench::state(SEED, dots);
+        bench::index(&mut state);
+        group.bench_function(BenchmarkId::from_parameter(dots), |b| {
+            b.iter(|| bench::attract(&state))
+        });
+    }
+    group.finish();
+}
+
+fn heat_death(c: &mut Criterion) {
+    let mut group = c.benchmark_group("heat_death");
+    for dots in DOTS {
+        group.bench_function(BenchmarkId::from_parameter(dots), |b| {
+            b.iter_batched(
+                || {
+                    let mut state = bench::state(SEED, dots);
+                    bench::desaturate(&mut state, 10);
+                    state
+                },
+                |mut state| {
+                    bench::heat_death(&mut state);
+                    state
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    group.sample_size(10);
+    for dots in DOTS {
+        let mut state = bench::state(SEED, dots);
+        state.tick();
+        group.bench_function(BenchmarkId::from_parameter(dots), |b| {
+            b.iter(|| state.tick())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, partition, neighbours, attract, heat_death, tick);
+criterion_main!(benches);
@@ -17,7 +17,7 @@ rm -f docs/${CRATE_NAME_SNAKE_CASE}_bg.wasm
 
 echo "Building rust…"
 BUILD=release
-cargo build --release -p ${CRATE_NAME} --lib --target wasm32-unknown-unknown
+cargo build --release -p ${CRATE_NAME} --lib --target wasm32-unknown-unknown --features gamepad
 
 echo "Generating JS bindings for wasm…"
 TARGET_NAME="${CRATE_NAME_SNAKE_CASE}.wasm"
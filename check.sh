@@ -1,6 +1,7 @@
 #!/bin/bash
 # This scripts runs various CI-like checks in a convenient way.
 set -eux
+# --all-features includes `gamepad`, which needs libudev on Linux.
 
 cargo check --workspace --all-targets
 cargo check --workspace --all-features --lib --target wasm32-unknown-unknown
@@ -0,0 +1,42 @@
+use crate::state::GameConfig;
+use std::{fs, path::PathBuf, time::SystemTime};
+
+/// Seconds between checks of the file modification time.
+const CHECK_INTERVAL: f64 = 1.0;
+
+/// Reloads `GameConfig` from a RON file whenever it changes on disk.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    next_check: f64,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            next_check: 0.0,
+        }
+    }
+    /// Returns new config if the file has changed since the last poll.
+    pub fn poll(&mut self, time: f64) -> Option<Result<GameConfig, String>> {
+        if time < self.next_check {
+            return None;
+        }
+        self.next_check = time + CHECK_INTERVAL;
+
+        let modified = fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .ok();
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let result = fs::read_to_string(&self.path)
+            .map_err(|err| err.to_string())
+            .and_then(|ron| GameConfig::from_ron(&ron).map_err(|err| err.to_string()))
+            .map_err(|err| format!("{}: {}", self.path.display(), err));
+        Some(result)
+    }
+}
@@ -0,0 +1,506 @@
+use super::{
+    gamepad::{PadButton, DEAD_ZONE},
+    pointer::PointerMode,
+};
+use crate::replay::Input;
+use eframe::egui::{self, InputState, Key};
+use glam::DVec2;
+
+/// What a local player asks of their core for a tick, all the world gets to see of the input.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerCommand {
+    /// Direction of the push, up to unit length.
+    pub steer: DVec2,
+    /// Ability button is held, a blackhole strips rivals from further away.
+    pub ability: bool,
+}
+
+impl From<DVec2> for PlayerCommand {
+    fn from(steer: DVec2) -> Self {
+        Self {
+            steer,
+            ability: false,
+        }
+    }
+}
+
+impl From<Input> for PlayerCommand {
+    fn from(input: Input) -> Self {
+        Self {
+            steer: input.steer,
+            ability: input.ability,
+        }
+    }
+}
+
+impl From<PlayerCommand> for Input {
+    fn from(command: PlayerCommand) -> Self {
+        Self {
+            steer: command.steer,
+            ability: command.ability,
+        }
+    }
+}
+
+/// Four keys steering one core.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyBindings {
+    pub up: Key,
+    pub down: Key,
+    pub left: Key,
+    pub right: Key,
+}
+
+impl KeyBindings {
+    pub const WASD: Self = Self::new(Key::W, Key::S, Key::A, Key::D);
+    pub const ARROWS: Self = Self::new(
+        Key::ArrowUp,
+        Key::ArrowDown,
+        Key::ArrowLeft,
+        Key::ArrowRight,
+    );
+    pub const IJKL: Self = Self::new(Key::I, Key::K, Key::J, Key::L);
+    pub const TFGH: Self = Self::new(Key::T, Key::G, Key::F, Key::H);
+
+    const fn new(up: Key, down: Key, left: Key, right: Key) -> Self {
+        Self {
+            up,
+            down,
+            left,
+            right,
+        }
+    }
+    fn key(&self, dir: Dir) -> Key {
+        match dir {
+            Dir::Up => self.up,
+            Dir::Down => self.down,
+            Dir::Left => self.left,
+            Dir::Right => self.right,
+        }
+    }
+    fn key_mut(&mut self, dir: Dir) -> &mut Key {
+        match dir {
+            Dir::Up => &mut self.up,
+            Dir::Down => &mut self.down,
+            Dir::Left => &mut self.left,
+            Dir::Right => &mut self.right,
+        }
+    }
+}
+
+/// Up to four players can share a keyboard.
+pub const MAX_PLAYERS: usize = 4;
+
+/// Key or typed character, egui has no keys for punctuation like the backtick.
+/// A character can only be pressed, not held.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum Trigger {
+    Key(Key),
+    Text(char),
+}
+
+impl Trigger {
+    /// Ctrl shortcuts are left to the app.
+    fn pressed(self, input: &InputState) -> bool {
+        if input.modifiers.command {
+            return false;
+        }
+        match self {
+            Trigger::Key(key) => input.key_pressed(key),
+            Trigger::Text(ch) => input
+                .events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Text(text) if text.starts_with(ch))),
+        }
+    }
+    fn held(self, input: &InputState) -> bool {
+        match self {
+            Trigger::Key(key) => input.key_down(key),
+            Trigger::Text(_) => false,
+        }
+    }
+    pub fn name(self) -> String {
+        match self {
+            Trigger::Key(key) => format!("{:?}", key),
+            Trigger::Text(ch) => ch.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Down, Dir::Left, Dir::Right];
+}
+
+/// Something a key or a button can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// Key set `set` steering in `dir`, a single player steers with the first two sets.
+    Steer {
+        set: usize,
+        dir: Dir,
+    },
+    Pause,
+    /// Held by the first player, the others have their gamepads.
+    Ability,
+    ZoomIn,
+    ZoomOut,
+    DebugPanel,
+    Overlay,
+    Camera,
+    /// Gamepad button which pauses.
+    PadPause,
+    PadAbility,
+}
+
+impl Control {
+    /// Everything bound to a key, in the order the controls screen lists them.
+    pub const KEYS: [Control; 7] = [
+        Control::Pause,
+        Control::Ability,
+        Control::ZoomIn,
+        Control::ZoomOut,
+        Control::Camera,
+        Control::DebugPanel,
+        Control::Overlay,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Control::Steer { .. } => "steer",
+            Control::Pause => "Pause",
+            Control::Ability => "Ability",
+            Control::ZoomIn => "Zoom in",
+            Control::ZoomOut => "Zoom out",
+            Control::DebugPanel => "Debug panel",
+            Control::Overlay => "Spatial index overlay",
+            Control::Camera => "Shared or split view",
+            Control::PadPause => "Gamepad pause",
+            Control::PadAbility => "Gamepad ability",
+        }
+    }
+}
+
+/// Keys and buttons of every control, with the rest of the input settings.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct InputMap {
+    /// Key set of every local player, a single player steers with the first two.
+    pub steering: [KeyBindings; MAX_PLAYERS],
+    pub pause: Trigger,
+    pub ability: Trigger,
+    pub zoom_in: Trigger,
+    pub zoom_out: Trigger,
+    pub debug_panel: Trigger,
+    pub overlay: Trigger,
+    pub camera: Trigger,
+    pub pad_pause: PadButton,
+    pub pad_ability: PadButton,
+    /// Stick travel which counts as letting go, the rest is stretched to the full range.
+    pub pad_dead_zone: f64,
+    pub pointer: PointerMode,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            steering: [
+                KeyBindings::WASD,
+                KeyBindings::ARROWS,
+                KeyBindings::IJKL,
+                KeyBindings::TFGH,
+            ],
+            pause: Trigger::Key(Key::Escape),
+            ability: Trigger::Key(Key::Space),
+            zoom_in: Trigger::Key(Key::PageUp),
+            zoom_out: Trigger::Key(Key::PageDown),
+            debug_panel: Trigger::Text('`'),
+            overlay: Trigger::Key(Key::P),
+            camera: Trigger::Key(Key::C),
+            pad_pause: PadButton::Start,
+            pad_ability: PadButton::South,
+            pad_dead_zone: DEAD_ZONE,
+            pointer: PointerMode::default(),
+        }
+    }
+}
+
+impl InputMap {
+    /// Key sets steering `player`.
+    fn sets(&self, player: usize, players: usize) -> &[KeyBindings] {
+        if players <= 1 {
+            &self.steering[..2]
+        } else {
+            std::slice::from_ref(&self.steering[player % MAX_PLAYERS])
+        }
+    }
+    /// Direction `player` holds with the keys.
+    pub fn steer(&self, input: &InputState, player: usize, players: usize) -> DVec2 {
+        keyboard_dir(input, self.sets(player, players))
+    }
+    pub fn pressed(&self, input: &InputState, control: Control) -> bool {
+        self.trigger(control)
+            .is_some_and(|trigger| trigger.pressed(input))
+    }
+    /// A typed character can't be held.
+    pub fn held(&self, input: &InputState, control: Control) -> bool {
+        self.trigger(control)
+            .is_some_and(|trigger| trigger.held(input))
+    }
+    /// Zoom factor for a frame of `dt` seconds with the zoom keys held.
+    pub fn zoom(&self, input: &InputState, dt: f64) -> f64 {
+        let held = |trigger: Trigger| trigger.held(input) as i32 as f64;
+        let zoom = held(self.zoom_in) - held(self.zoom_out);
+        (zoom * dt * 1.5).exp()
+    }
+    /// `None` for steering keys and gamepad buttons, which aren't triggers.
+    pub fn trigger(&self, control: Control) -> Option<Trigger> {
+        Some(match control {
+            Control::Pause => self.pause,
+            Control::Ability => self.ability,
+            Control::ZoomIn => self.zoom_in,
+            Control::ZoomOut => self.zoom_out,
+            Control::DebugPanel => self.debug_panel,
+            Control::Overlay => self.overlay,
+            Control::Camera => self.camera,
+            Control::Steer { .. } | Control::PadPause | Control::PadAbility => return None,
+        })
+    }
+    /// What `control` is bound to, for the controls screen.
+    pub fn binding(&self, control: Control) -> String {
+        match control {
+            Control::Steer { set, dir } => format!("{:?}", self.steering[set].key(dir)),
+            Control::PadPause => self.pad_pause.name().to_owned(),
+            Control::PadAbility => self.pad_ability.name().to_owned(),
+            _ => self.trigger(control).map(Trigger::name).unwrap_or_default(),
+        }
+    }
+    /// Binds `control` to what was pressed this frame, returns `false` while nothing was.
+    /// Steering only takes keys, gamepad controls only take buttons.
+    /// A key or a button bound to another control swaps with it.
+    pub fn rebind(
+        &mut self,
+        input: &InputState,
+        pressed: Option<PadButton>,
+        control: Control,
+    ) -> bool {
+        if let Control::PadPause | Control::PadAbility = control {
+            let button = match pressed {
+                Some(button) => button,
+                None => return false,
+            };
+            let (own, other) = match control {
+                Control::PadPause => (&mut self.pad_pause, &mut self.pad_ability),
+                _ => (&mut self.pad_ability, &mut self.pad_pause),
+            };
+            if *other == button {
+                *other = *own;
+            }
+            *own = button;
+            return true;
+        }
+        let key = input.events.iter().find_map(|event| match event {
+            egui::Event::Key {
+                key, pressed: true, ..
+            } => Some(*key),
+            _ => None,
+        });
+        let text = input.events.iter().find_map(|event| match event {
+            egui::Event::Text(text) => text.chars().next(),
+            _ => None,
+        });
+        let trigger = match (key, text) {
+            (Some(key), _) => Trigger::Key(key),
+            (None, Some(ch)) => Trigger::Text(ch),
+            (None, None) => return false,
+        };
+        let previous = self.bound(control);
+        let taken = Self::bound_controls()
+            .find(|&other| other != control && self.bound(other) == Some(trigger));
+        match (taken, previous) {
+            (Some(other), Some(previous)) => {
+                if !self.set(control, trigger) {
+                    return false;
+                }
+                // Steering can't take a typed character, both keep their bindings.
+                if !self.set(other, previous) {
+                    self.set(control, previous);
+                    return false;
+                }
+            }
+            _ => return self.set(control, trigger),
+        }
+        true
+    }
+    /// Every control bound to a key or a typed character.
+    fn bound_controls() -> impl Iterator<Item = Control> {
+        let steering = (0..MAX_PLAYERS)
+            .flat_map(|set| Dir::ALL.iter().map(move |&dir| Control::Steer { set, dir }));
+        steering.chain(Control::KEYS.iter().copied())
+    }
+    fn bound(&self, control: Control) -> Option<Trigger> {
+        match control {
+            Control::Steer { set, dir } => Some(Trigger::Key(self.steering[set].key(dir))),
+            _ => self.trigger(control),
+        }
+    }
+    /// Returns `false` if `control` can't take `trigger`.
+    fn set(&mut self, control: Control, trigger: Trigger) -> bool {
+        match (control, trigger) {
+            (Control::Steer { set, dir }, Trigger::Key(key)) => {
+                *self.steering[set].key_mut(dir) = key;
+            }
+            (Control::Steer { .. }, Trigger::Text(_))
+            | (Control::PadPause, _)
+            | (Control::PadAbility, _) => return false,
+            (Control::Pause, _) => self.pause = trigger,
+            (Control::Ability, _) => self.ability = trigger,
+            (Control::ZoomIn, _) => self.zoom_in = trigger,
+            (Control::ZoomOut, _) => self.zoom_out = trigger,
+            (Control::DebugPanel, _) => self.debug_panel = trigger,
+            (Control::Overlay, _) => self.overlay = trigger,
+            (Control::Camera, _) => self.camera = trigger,
+        }
+        true
+    }
+}
+
+/// Direction held with any of `bindings`, diagonals are scaled down.
+fn keyboard_dir(input: &InputState, bindings: &[KeyBindings]) -> DVec2 {
+    let down = |key: fn(&KeyBindings) -> Key| bindings.iter().any(|b| input.key_down(key(b)));
+    let left = down(|b| b.left);
+    let right = down(|b| b.right);
+    let up = down(|b| b.up);
+    let down = down(|b| b.down);
+
+    let left_xor_right = left ^ right;
+    let up_xor_down = up ^ down;
+
+    let mul = if left_xor_right ^ up_xor_down {
+        1.0
+    } else if left_xor_right && up_xor_down {
+        0.7
+    } else {
+        return DVec2::new(0.0, 0.0);
+    };
+    let x = if left_xor_right {
+        if left {
+            -mul
+        } else {
+            mul
+        }
+    } else {
+        0.0
+    };
+    let y = if up_xor_down {
+        if down {
+            -mul
+        } else {
+            mul
+        }
+    } else {
+        0.0
+    };
+    DVec2::new(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pressing(events: Vec<egui::Event>) -> InputState {
+        let mut input = InputState::default();
+        input.events = events;
+        input
+    }
+    fn key(key: Key) -> InputState {
+        pressing(vec![egui::Event::Key {
+            key,
+            pressed: true,
+            modifiers: egui::Modifiers::default(),
+        }])
+    }
+    fn text(ch: char) -> InputState {
+        pressing(vec![egui::Event::Text(ch.to_string())])
+    }
+
+    const UP: Control = Control::Steer {
+        set: 0,
+        dir: Dir::Up,
+    };
+
+    #[test]
+    fn steering_refuses_a_typed_character() {
+        let mut map = InputMap::default();
+        assert!(!map.rebind(&text('x'), None, UP));
+        // Taken by the debug panel, still not a key.
+        assert!(!map.rebind(&text('`'), None, UP));
+        assert_eq!(map, InputMap::default());
+    }
+
+    #[test]
+    fn taken_key_swaps() {
+        let mut map = InputMap::default();
+        assert!(map.rebind(&key(Key::Escape), None, UP));
+        assert_eq!(map.steering[0].up, Key::Escape);
+        assert_eq!(map.pause, Trigger::Key(Key::W));
+        assert!(map.rebind(&key(Key::ArrowUp), None, UP));
+        assert_eq!(map.steering[0].up, Key::ArrowUp);
+        assert_eq!(map.steering[1].up, Key::Escape);
+    }
+
+    #[test]
+    fn failed_swap_keeps_both_bindings() {
+        let mut map = InputMap::default();
+        // Steering would have to take the backtick.
+        assert!(!map.rebind(&key(Key::W), None, Control::DebugPanel));
+        assert_eq!(map, InputMap::default());
+        assert!(!map.rebind(&pressing(vec![]), None, Control::Pause));
+        assert_eq!(map, InputMap::default());
+    }
+
+    #[test]
+    fn gamepad_buttons_swap() {
+        let mut map = InputMap::default();
+        assert!(!map.rebind(&key(Key::Space), None, Control::PadPause));
+        assert!(!map.rebind(&pressing(vec![]), Some(PadButton::North), Control::Pause));
+        assert_eq!(map, InputMap::default());
+
+        assert!(map.rebind(&pressing(vec![]), Some(PadButton::South), Control::PadPause));
+        assert_eq!(
+            (map.pad_pause, map.pad_ability),
+            (PadButton::South, PadButton::Start)
+        );
+        assert!(map.rebind(
+            &pressing(vec![]),
+            Some(PadButton::South),
+            Control::PadAbility
+        ));
+        assert_eq!(
+            (map.pad_pause, map.pad_ability),
+            (PadButton::Start, PadButton::South)
+        );
+        assert!(map.rebind(
+            &pressing(vec![]),
+            Some(PadButton::West),
+            Control::PadAbility
+        ));
+        assert_eq!(
+            (map.pad_pause, map.pad_ability),
+            (PadButton::Start, PadButton::West)
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use super::Game;
+use crate::state::{GameConfig, SpatialIndex};
+use eframe::egui::{self, Slider};
+use std::ops::RangeInclusive;
+
+/// Side panel with live readouts and sliders for `GameConfig`.
+#[derive(Default)]
+pub struct DebugPanel {
+    open: bool,
+}
+
+impl DebugPanel {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    pub fn show(&mut self, ctx: &egui::CtxRef, game: &mut Game) {
+        if !self.open {
+            return;
+        }
+        egui::SidePanel::left("debug").show(ctx, |ui| {
+            egui::ScrollArea::auto_sized().show(ui, |ui| {
+                Self::readouts(ui, game);
+                ui.separator();
+                Self::sliders(ui, game.state.config_mut());
+            });
+        });
+    }
+    fn readouts(ui: &mut egui::Ui, game: &Game) {
+        let state = &game.state;
+        let stats = state.tick_stats();
+        egui::Grid::new("debug_stats").show(ui, |ui| {
+            let mut row = |name: &str, value: String| {
+                ui.label(name);
+                ui.label(value);
+                ui.end_row();
+            };
+            row("ticks", game.ticks.to_string());
+            row("round ticks", state.round_ticks().to_string());
+            row("entities", state.entity_count().to_string());
+            row("free dots", state.free_dots().to_string());
+            row("tick time", format!("{:.2} ms", millis(stats.tick_time)));
+            row(
+                "neighbours time",
+                format!("{:.2} ms", millis(stats.neighbours_time)),
+            );
+            row("neighbour radius", format!("{:.2}", stats.neighbour_radius));
+            row("index cells", stats.cells.to_string());
+            for (i, &player) in game.players.iter().enumerate() {
+                let player = match state.player_stats(player) {
+                    Some(player) => player,
+                    None => continue,
+                };
+                if game.players.len() > 1 {
+                    row("player", (i + 1).to_string());
+                }
+                row("energy", format!("{:.4}", player.energy));
+                row("energy size", format!("{:.4}", player.energy_size));
+                row("size", format!("{:.4}", player.size));
+                row("orbiting dots", player.orbiting_dots.to_string());
+                row("blackhole", player.blackhole.to_string());
+            }
+        });
+    }
+    fn sliders(ui: &mut egui::Ui, config: &mut GameConfig) {
+        let physics = &mut config.physics;
+        ui.collapsing("Physics", |ui| {
+            log_slider(ui, &mut physics.gravity, 0.00001..=0.1, "gravity");
+            ui.add(Slider::new(&mut physics.deceleration, 0.0..=1.0).text("deceleration"));
+            ui.add(Slider::new(&mut physics.hue_offset, 0.0..=180.0).text("hue offset"));
+            ui.add(Slider::new(&mut physics.hue_falloff, 1.0..=360.0).text("hue falloff"));
+            ui.add(Slider::new(&mut physics.repulsion_radius, 0.0..=2.0).text("repulsion radius"));
+            log_slider(
+                ui,
+                &mut physics.repulsion_force,
+                0.000001..=0.01,
+                "repulsion force",
+            );
+            log_slider(
+                ui,
+                &mut physics.attraction_force,
+                0.0001..=1.0,
+                "attraction force",
+            );
+            log_slider(
+                ui,
+                &mut physics.neighbour_budget,
+                100.0..=100000.0,
+                "neighbour budget",
+            );
+            ui.add(
+                Slider::new(&mut physics.min_neighbour_radius, 0.1..=50.0)
+                    .text("min neighbour radius"),
+            );
+            ui.horizontal(|ui| {
+                ui.label("spatial index");
+                ui.radio_value(&mut physics.spatial_index, SpatialIndex::Grid, "grid");
+                ui.radio_value(
+                    &mut physics.spatial_index,
+                    SpatialIndex::Partition,
+                    "partition",
+                );
+            });
+        });
+        let player = &mut config.player;
+        ui.collapsing("Player", |ui| {
+            log_slider(ui, &mut player.input_impulse, 0.0001..=0.1, "input impulse");
+            log_slider(
+                ui,
+                &mut player.rotation_speed,
+                0.00001..=0.1,
+                "rotation speed",
+            );
+            ui.add(Slider::new(&mut player.consume_radius, 0.0..=2.0).text("consume radius"));
+            log_slider(
+                ui,
+                &mut player.saturation_drain,
+                0.0001..=1.0,
+                "saturation drain",
+            );
+            log_slider(
+                ui,
+                &mut player.energy_per_drain,
+                0.000001..=0.1,
+                "energy per drain",
+            );
+            ui.add(Slider::new(&mut player.blackhole_size, 1.5..=30.0).text("blackhole size"));
+        });
+        ui.collapsing("Rivals", |ui| {
+            ui.add(Slider::new(&mut player.strip_radius, 0.0..=5.0).text("strip radius"));
+            ui.add(
+                Slider::new(&mut player.ability_strip_radius, 0.0..=10.0)
+                    .text("ability strip radius"),
+            );
+            ui.add(Slider::new(&mut player.strip_cooldown, 0..=300).text("strip cooldown"));
+            ui.add(Slider::new(&mut player.rival_hue_range, 0.0..=180.0).text("rival hue range"));
+            ui.add(
+                Slider::new(&mut player.rival_repulsion_radius, 0.0..=5.0)
+                    .text("rival repulsion radius"),
+            );
+            log_slider(
+                ui,
+                &mut player.rival_repulsion_force,
+                0.0001..=0.1,
+                "rival repulsion force",
+            );
+        });
+        let round = &mut config.round;
+        ui.collapsing("Round", |ui| {
+            ui.add(
+                Slider::new(&mut round.time_attack_ticks, 600..=36000).text("time attack ticks"),
+            );
+            log_slider(
+                ui,
+                &mut round.survival_upkeep,
+                0.00001..=0.01,
+                "survival upkeep",
+            );
+            ui.add(Slider::new(&mut round.survival_min_size, 0.0..=1.0).text("survival min size"));
+        });
+        if ui.button("Reset to defaults").clicked() {
+            *config = GameConfig::default();
+        }
+    }
+}
+
+fn log_slider(ui: &mut egui::Ui, value: &mut f64, range: RangeInclusive<f64>, text: &str) {
+    ui.add(Slider::new(value, range).logarithmic(true).text(text));
+}
+
+fn millis(duration: instant::Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,90 @@
+use super::{scene::SceneDot, view::View};
+use eframe::egui::{epaint::Mesh, vec2, Color32, Pos2, Rect, Vec2};
+
+/// Dots up to this radius in pixels are drawn as a square, a circle wouldn't look any rounder.
+const QUAD_RADIUS: f32 = 1.5;
+/// Sides of a circle, by the largest radius in pixels they are used for.
+const SIDES: [(f32, usize); 3] = [(4.0, 8), (8.0, 16), (f32::INFINITY, 32)];
+/// Width in pixels of the transparent rim which smooths the edge of a circle.
+const FEATHER: f32 = 1.0;
+/// Width in pixels of the outline of a hollow dot.
+const HOLLOW_STROKE: f32 = 0.5;
+
+/// Every dot in `view` as a single mesh, dots off the view are left out.
+/// Tessellating thousands of circle shapes each frame is too slow for the web.
+pub fn mesh(view: View, dots: &[SceneDot]) -> Mesh {
+    let outlines = Outlines::new();
+    let mut mesh = Mesh::default();
+    mesh.reserve_vertices(dots.len() * 4);
+    mesh.reserve_triangles(dots.len() * 2);
+    for dot in dots {
+        let radius = dot.size * 3.0;
+        let center = view.to_screen(dot.pos);
+        if !view.rect.expand(radius + FEATHER).contains(center) {
+            continue;
+        }
+        if dot.hollow {
+            let half = HOLLOW_STROKE * 0.5;
+            disk(&mut mesh, &outlines, center, radius + half, dot.color);
+            disk(&mut mesh, &outlines, center, radius - half, Color32::BLACK);
+        } else {
+            disk(&mut mesh, &outlines, center, radius, dot.color);
+        }
+    }
+    mesh
+}
+
+/// Unit circle for every entry of `SIDES`.
+struct Outlines(Vec<Vec<Vec2>>);
+
+impl Outlines {
+    fn new() -> Self {
+        let outline = |sides| {
+            (0..sides)
+                .map(|i| Vec2::angled(i as f32 * std::f32::consts::TAU / sides as f32))
+                .collect()
+        };
+        Self(SIDES.iter().map(|&(_, sides)| outline(sides)).collect())
+    }
+    fn get(&self, radius: f32) -> &[Vec2] {
+        let level = SIDES
+            .iter()
+            .position(|&(largest, _)| radius <= largest)
+            .unwrap_or(SIDES.len() - 1);
+        &self.0[level]
+    }
+}
+
+/// Square as big as the disk for tiny ones, a feathered triangle fan for the rest.
+fn disk(mesh: &mut Mesh, outlines: &Outlines, center: Pos2, radius: f32, color: Color32) {
+    if radius <= 0.0 {
+        return;
+    }
+    if radius <= QUAD_RADIUS {
+        // Half side of a square with the area of the disk.
+        let half = radius * std::f32::consts::PI.sqrt() * 0.5;
+        let rect = Rect::from_center_size(center, vec2(half, half) * 2.0);
+        mesh.add_colored_rect(rect, color);
+        return;
+    }
+    let outline = outlines.get(radius);
+    let sides = outline.len() as u32;
+    let first = mesh.vertices.len() as u32;
+    mesh.colored_vertex(center, color);
+    for &unit in outline {
+        mesh.colored_vertex(center + unit * (radius - FEATHER * 0.5), color);
+    }
+    for &unit in outline {
+        mesh.colored_vertex(
+            center + unit * (radius + FEATHER * 0.5),
+            Color32::TRANSPARENT,
+        );
+    }
+    let (inner, outer) = (first + 1, first + 1 + sides);
+    for i in 0..sides {
+        let j = (i + 1) % sides;
+        mesh.add_triangle(first, inner + i, inner + j);
+        mesh.add_triangle(inner + i, outer + i, outer + j);
+        mesh.add_triangle(inner + i, outer + j, inner + j);
+    }
+}
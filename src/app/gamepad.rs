@@ -0,0 +1,147 @@
+use glam::DVec2;
+
+/// Default length of stick travel which counts as letting go.
+pub const DEAD_ZONE: f64 = 0.2;
+
+/// What a gamepad asks for this frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PadInput {
+    /// Left stick or d-pad, up to unit length.
+    pub dir: DVec2,
+    /// Button pressed since the last frame, one of those a control can be bound to.
+    pub pressed: Option<PadButton>,
+    /// Buttons held down, of those a control can be bound to.
+    pub held: Vec<PadButton>,
+}
+
+/// Gamepad buttons a control can be bound to, named by their place on the pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    Select,
+    Start,
+}
+
+impl PadButton {
+    pub fn name(self) -> &'static str {
+        match self {
+            PadButton::South => "South (A)",
+            PadButton::East => "East (B)",
+            PadButton::North => "North (Y)",
+            PadButton::West => "West (X)",
+            PadButton::Select => "Select",
+            PadButton::Start => "Start",
+        }
+    }
+    #[cfg(feature = "gamepad")]
+    fn of(button: gilrs::Button) -> Option<Self> {
+        use gilrs::Button;
+        Some(match button {
+            Button::South => PadButton::South,
+            Button::East => PadButton::East,
+            Button::North => PadButton::North,
+            Button::West => PadButton::West,
+            Button::Select => PadButton::Select,
+            Button::Start => PadButton::Start,
+            _ => return None,
+        })
+    }
+}
+
+/// Connected gamepads, the first one steers the first local player and so on.
+/// Browsers without the Gamepad API and builds without the `gamepad` feature have none.
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+#[cfg_attr(not(feature = "gamepad"), allow(clippy::derivable_impls))]
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self {
+            // Our own dead zone replaces the filters of gilrs.
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::GilrsBuilder::new()
+                .with_default_filters(false)
+                .build()
+                .ok(),
+        }
+    }
+}
+
+impl Gamepads {
+    /// Input of every connected gamepad, called once a frame.
+    /// Stick travel within `dead_zone` counts as letting go, the rest is stretched to the full range.
+    #[cfg_attr(not(feature = "gamepad"), allow(unused_variables))]
+    pub fn poll(&mut self, dead_zone: f64) -> Vec<PadInput> {
+        #[cfg(feature = "gamepad")]
+        if let Some(gilrs) = &mut self.gilrs {
+            use gilrs::{Axis, Button, EventType};
+
+            let mut pressed = vec![];
+            while let Some(event) = gilrs.next_event() {
+                if let EventType::ButtonPressed(button, _) = event.event {
+                    if let Some(button) = PadButton::of(button) {
+                        pressed.push((event.id, button));
+                    }
+                }
+            }
+            return gilrs
+                .gamepads()
+                .map(|(id, pad)| {
+                    let button = |button| pad.is_pressed(button) as i32 as f64;
+                    let dpad = DVec2::new(
+                        button(Button::DPadRight) - button(Button::DPadLeft),
+                        button(Button::DPadUp) - button(Button::DPadDown),
+                    );
+                    let stick = DVec2::new(
+                        pad.value(Axis::LeftStickX) as f64,
+                        pad.value(Axis::LeftStickY) as f64,
+                    );
+                    let dir = if dpad != DVec2::ZERO {
+                        dpad.normalize()
+                    } else {
+                        outside_dead_zone(stick, dead_zone)
+                    };
+                    let buttons = [
+                        Button::South,
+                        Button::East,
+                        Button::North,
+                        Button::West,
+                        Button::Select,
+                        Button::Start,
+                    ];
+                    PadInput {
+                        dir,
+                        pressed: pressed
+                            .iter()
+                            .find(|&&(pad, _)| pad == id)
+                            .map(|&(_, button)| button),
+                        held: buttons
+                            .iter()
+                            .filter(|&&button| pad.is_pressed(button))
+                            .filter_map(|&button| PadButton::of(button))
+                            .collect(),
+                    }
+                })
+                .collect();
+        }
+        vec![]
+    }
+}
+
+/// Nothing within `dead_zone` of the middle, the travel beyond it goes from nothing up to unit length.
+#[cfg(feature = "gamepad")]
+fn outside_dead_zone(stick: DVec2, dead_zone: f64) -> DVec2 {
+    let length = stick.length();
+    if length <= dead_zone {
+        return DVec2::ZERO;
+    }
+    let strength = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    stick / length * strength
+}
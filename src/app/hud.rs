@@ -0,0 +1,191 @@
+use super::{round::clock, scene::hsl_color, scene::Scene, Game};
+use crate::state::{PhysicsConfig, PlayerStats, Score, Standing, HUES};
+use eframe::egui::{self, Align2, Color32, ProgressBar, Rect, Sense, Stroke, Vec2};
+use glam::DVec2;
+
+/// Hue circle of the color wheel is drawn in slices this wide, in degrees.
+const WHEEL_SLICE: usize = 10;
+/// Minimap counts free dots in this many cells across.
+const MINIMAP_CELLS: usize = 32;
+
+/// Growth and score of every local player in the bottom left corner.
+pub fn stats(ctx: &egui::CtxRef, game: &Game, dt: f64) {
+    let state = &game.state;
+    egui::Area::new("stats")
+        .anchor(Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for &core in &game.players {
+                    if let Some(standing) = state.score(core) {
+                        ui.vertical(|ui| {
+                            if game.players.len() > 1 {
+                                ui.label(egui::Label::new(game.label(core)).strong());
+                            }
+                            if let Some(stats) = state.player_stats(core) {
+                                let hues = state.orbiting_hues(core);
+                                let config = state.config();
+                                growth(ui, &stats, config.player.blackhole_size);
+                                wheel(ui, &hues, &config.physics);
+                            }
+                            player(ui, standing, dt);
+                        });
+                        ui.add_space(10.0);
+                    }
+                }
+            });
+        });
+}
+
+/// Energy toward the next orbiting dot and size toward the blackhole.
+fn growth(ui: &mut egui::Ui, stats: &PlayerStats, blackhole_size: f64) {
+    let width = 12.0 * HUES as f32;
+    // `Player::grow` adds an orbiting dot once the energy size reaches their count plus one.
+    let dots = stats.orbiting_dots as f64;
+    let (from, to) = (dots * dots, (dots + 1.0) * (dots + 1.0));
+    let bar = if stats.blackhole {
+        ProgressBar::new(1.0).text("blackhole")
+    } else {
+        let progress = ((stats.energy - from) / (to - from)).clamp(0.0, 1.0);
+        let text = format!("energy {:.2} / {:.0}", stats.energy, to);
+        ProgressBar::new(progress as f32).text(text)
+    };
+    ui.add_sized([width, 16.0], bar);
+    let progress = (stats.energy_size / blackhole_size).min(1.0);
+    let text = format!("size {:.2} / {:.0}", stats.energy_size, blackhole_size);
+    ui.add_sized([width, 16.0], ProgressBar::new(progress as f32).text(text));
+}
+
+/// Hue circle, lit where free dots are pulled in by an orbiting dot, with the orbiting dots on it.
+fn wheel(ui: &mut egui::Ui, hues: &[f64], physics: &PhysicsConfig) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::splat(64.0), Sense::hover());
+    let painter = ui.painter();
+    let center = rect.center();
+    let radius = rect.width() * 0.5 - 6.0;
+    let at = |hue: f64| {
+        let angle = (hue - 90.0).to_radians() as f32;
+        center + Vec2::new(angle.cos(), angle.sin()) * radius
+    };
+    for start in (0..360).step_by(WHEEL_SLICE) {
+        let hue = (start + WHEEL_SLICE / 2) as f64;
+        let pulled = hues.iter().any(|&own| physics.attracts(own, hue));
+        let lightness = if pulled { 50.0 } else { 15.0 };
+        let end = (start + WHEEL_SLICE) as f64;
+        let stroke = Stroke::new(8.0, hsl_color(hue, 100.0, lightness));
+        painter.line_segment([at(start as f64), at(end)], stroke);
+    }
+    for &hue in hues {
+        painter.circle(
+            at(hue),
+            4.0,
+            hsl_color(hue, 100.0, 50.0),
+            Stroke::new(1.5, Color32::WHITE),
+        );
+    }
+}
+
+fn player(ui: &mut egui::Ui, standing: &Standing, dt: f64) {
+    let score = &standing.score;
+    ui.label(format!(
+        "energy {:.2}  gained {:.2}",
+        standing.energy, score.energy_gained
+    ));
+    ui.label(format!(
+        "dots drained {}  peak size {:.2}",
+        score.dots_drained, score.peak_size
+    ));
+    if let Some(&at) = score.milestones.last() {
+        ui.label(format!(
+            "orbiting dot {} at {}",
+            score.milestones.len(),
+            clock(at, dt)
+        ));
+    }
+    if let Some(at) = standing.blackhole_at {
+        ui.label(format!("blackhole at {}", clock(at, dt)));
+    }
+    hues(ui, score);
+}
+
+/// Bar for every slice of the hue circle, as tall as the energy drained from it.
+fn hues(ui: &mut egui::Ui, score: &Score) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(12.0 * HUES as f32, 24.0), Sense::hover());
+    let painter = ui.painter();
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, ui.visuals().weak_text_color()));
+    let most = score.hues.iter().cloned().fold(0.0, f64::max);
+    if most <= 0.0 {
+        return;
+    }
+    let width = rect.width() / HUES as f32;
+    for (i, &energy) in score.hues.iter().enumerate() {
+        let height = (energy / most) as f32 * rect.height();
+        let left = rect.left() + i as f32 * width;
+        let bar = Rect::from_min_max(
+            [left, rect.bottom() - height].into(),
+            [left + width, rect.bottom()].into(),
+        );
+        let hue = (i as f64 + 0.5) * 360.0 / HUES as f64;
+        painter.rect_filled(bar, 0.0, hsl_color(hue, 100.0, 50.0));
+    }
+}
+
+/// Density of free dots around the middle of the world, with a mark for every core.
+pub fn minimap(ctx: &egui::CtxRef, scene: &Scene) {
+    let extent = scene
+        .dots
+        .iter()
+        .filter(|dot| !dot.hollow)
+        .map(|dot| dot.pos.abs().max_element())
+        .fold(10.0, f64::max);
+    let mut counts = vec![0u32; MINIMAP_CELLS * MINIMAP_CELLS];
+    let cell = |pos: DVec2| {
+        let scaled = on_map(pos, extent) * MINIMAP_CELLS as f64;
+        let x = (scaled.x as usize).min(MINIMAP_CELLS - 1);
+        let y = (scaled.y as usize).min(MINIMAP_CELLS - 1);
+        (x, y)
+    };
+    for dot in scene.dots.iter().filter(|dot| !dot.hollow) {
+        let (x, y) = cell(dot.pos);
+        counts[y * MINIMAP_CELLS + x] += 1;
+    }
+    let most = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+
+    egui::Area::new("minimap")
+        .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .show(ctx, |ui| {
+            let (rect, _) = ui.allocate_exact_size(Vec2::splat(128.0), Sense::hover());
+            let painter = ui.painter();
+            painter.rect_filled(rect, 0.0, Color32::from_black_alpha(160));
+            let size = rect.width() / MINIMAP_CELLS as f32;
+            for (i, &count) in counts.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+                let min = rect.min
+                    + Vec2::new((i % MINIMAP_CELLS) as f32, (i / MINIMAP_CELLS) as f32) * size;
+                let alpha = 40.0 + 215.0 * (count as f32 / most).sqrt();
+                let fill = Color32::from_white_alpha(alpha as u8);
+                painter.rect_filled(Rect::from_min_size(min, Vec2::splat(size)), 0.0, fill);
+            }
+            let to_map = |pos: DVec2| {
+                let scaled = on_map(pos, extent);
+                rect.min + Vec2::new(scaled.x as f32, scaled.y as f32) * rect.width()
+            };
+            for core in scene
+                .players
+                .iter()
+                .filter_map(|player| player.core.as_ref())
+            {
+                painter.circle_stroke(to_map(core.pos), 3.0, Stroke::new(1.0, Color32::GRAY));
+            }
+            for focus in scene.followed.iter().flatten() {
+                painter.circle_filled(to_map(focus.pos), 3.0, Color32::YELLOW);
+            }
+            painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::GRAY));
+        });
+}
+
+/// Position on the minimap from its top left corner to the bottom right, up like in the view.
+fn on_map(pos: DVec2, extent: f64) -> DVec2 {
+    let unit = (DVec2::new(pos.x, -pos.y) / extent + 1.0) * 0.5;
+    unit.clamp(DVec2::ZERO, DVec2::ONE)
+}
@@ -0,0 +1,920 @@
+use crate::{
+    replay::Replay,
+    state::{self, Difficulty, GameMode},
+};
+use controls::{Control, InputMap, PlayerCommand};
+use debug::DebugPanel;
+use eframe::{
+    egui::{self, Color32, Frame},
+    epi,
+};
+use gamepad::{Gamepads, PadButton, PadInput};
+use glam::DVec2;
+use hecs::Entity;
+use overlay::PartitionOverlay;
+use pointer::PointerSteering;
+use scene::Scene;
+use scores::HighScores;
+use screen::{Action, Screen, Setup};
+use timestep::FixedTimestep;
+use view::{Camera, CameraMode, View};
+use viewer::ReplayViewer;
+
+#[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+mod config_watcher;
+mod controls;
+mod debug;
+mod dots;
+mod gamepad;
+mod hud;
+mod overlay;
+mod pointer;
+mod round;
+mod scene;
+mod scores;
+mod screen;
+mod timestep;
+mod view;
+mod viewer;
+
+pub struct App {
+    game: Game,
+    timestep: FixedTimestep,
+    mode: Mode,
+    screen: Screen,
+    camera: CameraMode,
+    /// One per view, made again when the views change.
+    cameras: Vec<Camera>,
+    /// View of the last frame and the core it followed, when there was one of each.
+    aim: Option<(View, DVec2)>,
+    input_map: InputMap,
+    /// Control of the controls screen waiting for a key or a button.
+    rebinding: Option<Control>,
+    pointer: PointerSteering,
+    gamepads: Gamepads,
+    setup: Setup,
+    /// Zen restarts already summarized.
+    restarts: u32,
+    scores: HighScores,
+    /// Players of the last round who made the high scores, with their place.
+    records: Vec<(String, usize)>,
+    notice: Option<(String, f64)>,
+    /// Server to join on the next frame.
+    #[cfg(feature = "net")]
+    join: Option<String>,
+    debug: DebugPanel,
+    overlay: PartitionOverlay,
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    config_watcher: config_watcher::ConfigWatcher,
+}
+
+enum Mode {
+    /// Normal play, input of every tick is recorded.
+    /// A loaded game isn't recorded, it can't be reproduced from a seed.
+    Play(Option<Replay>),
+    Replay(ReplayViewer),
+    /// Playing on a server, the local game waits until the connection is gone.
+    #[cfg(feature = "net")]
+    Online(Box<crate::net::Client>),
+}
+
+/// Zoom factor of a pixel of mouse wheel scroll, as a power of e.
+const ZOOM_PER_SCROLL: f64 = 0.002;
+/// Number of AI rivals in a game with rivals.
+const RIVALS: usize = 3;
+#[cfg(feature = "persistence")]
+const GAME_KEY: &str = "game";
+#[cfg(feature = "persistence")]
+const INPUT_KEY: &str = "input_map";
+#[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+const SAVE_PATH: &str = "core_of_color.save.ron";
+#[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+const SCORES_PATH: &str = "core_of_color.scores.ron";
+#[cfg(all(feature = "persistence", target_arch = "wasm32"))]
+const SCORES_KEY: &str = "scores";
+#[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+const CONFIG_PATH: &str = "config.ron";
+
+#[cfg(feature = "persistence")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedGame {
+    state: state::SavedState,
+    players: Vec<state::SavedEntity>,
+    ticks: usize,
+    tick: f64,
+}
+
+struct Game {
+    state: state::State,
+    /// Cores of local players, in the order of their key bindings.
+    players: Vec<Entity>,
+    /// AI rivals the game started with, eliminated ones included.
+    bots: Vec<(Entity, Difficulty)>,
+    /// Number of simulated ticks.
+    ticks: usize,
+    /// Phase of the core animation.
+    tick: f64,
+}
+
+impl Game {
+    fn new(
+        seed: u64,
+        config: state::GameConfig,
+        players: usize,
+        bots: &[Difficulty],
+        mode: GameMode,
+    ) -> Self {
+        let (mut state, players) = state::State::new_game_with_bots(seed, players, bots);
+        *state.config_mut() = config;
+        state.set_mode(mode);
+        Self {
+            bots: state.bots(),
+            state,
+            players,
+            ticks: 0,
+            tick: 0.0,
+        }
+    }
+    /// `P1`, `P2` and so on for players, `AI 1` and so on for AI rivals.
+    fn label(&self, core: Entity) -> String {
+        if let Some(i) = self.players.iter().position(|&player| player == core) {
+            return format!("P{}", i + 1);
+        }
+        match self.bots.iter().position(|&(bot, _)| bot == core) {
+            Some(i) => format!("AI {} ({})", i + 1, self.bots[i].1.name()),
+            None => "?".to_owned(),
+        }
+    }
+    /// `commands` has a command for every player.
+    fn step(&mut self, commands: &[PlayerCommand]) {
+        for (&player, command) in self.players.iter().zip(commands) {
+            self.state.player_input(player, command.steer);
+            self.state.player_ability(player, command.ability);
+        }
+        self.state.tick();
+        self.ticks += 1;
+        self.tick += 0.1;
+    }
+    #[cfg(feature = "persistence")]
+    fn save(&self) -> SavedGame {
+        SavedGame {
+            state: self.state.save(),
+            players: self.players.iter().map(|&player| player.into()).collect(),
+            ticks: self.ticks,
+            tick: self.tick,
+        }
+    }
+    #[cfg(feature = "persistence")]
+    fn load(saved: SavedGame) -> Option<Self> {
+        let (state, remap) = state::State::load(saved.state);
+        let players = saved
+            .players
+            .into_iter()
+            .map(|player| remap.get(player))
+            .collect::<Option<Vec<_>>>()?;
+        if players.is_empty() {
+            return None;
+        }
+        Some(Self {
+            bots: state.bots(),
+            state,
+            players,
+            ticks: saved.ticks,
+            tick: saved.tick,
+        })
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App {
+    pub fn new() -> Self {
+        let game = Game::new(rand::random(), Default::default(), 1, &[], GameMode::Zen);
+        let recording = Replay::new(game.state.seed(), 1);
+        #[allow(unused_mut)]
+        let mut app = Self {
+            game,
+            timestep: FixedTimestep::default(),
+            mode: Mode::Play(Some(recording)),
+            screen: Screen::Title,
+            camera: CameraMode::default(),
+            cameras: vec![],
+            aim: None,
+            input_map: InputMap::default(),
+            rebinding: None,
+            pointer: PointerSteering::default(),
+            gamepads: Gamepads::default(),
+            setup: Setup::default(),
+            restarts: 0,
+            scores: HighScores::default(),
+            records: vec![],
+            notice: None,
+            #[cfg(feature = "net")]
+            join: None,
+            debug: DebugPanel::default(),
+            overlay: PartitionOverlay::default(),
+            #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+            config_watcher: config_watcher::ConfigWatcher::new(CONFIG_PATH),
+        };
+        #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+        match HighScores::load(SCORES_PATH) {
+            Ok(scores) => app.scores = scores,
+            Err(err) => {
+                let text = format!("Failed to load high scores: {}", err);
+                app.notice = Some((text, 3.0));
+            }
+        }
+        app
+    }
+    /// Watch a recorded session instead of playing.
+    pub fn with_replay(replay: Replay) -> Self {
+        Self {
+            game: Game::new(
+                replay.seed,
+                replay.config_at(0),
+                replay.players(),
+                &replay.bots,
+                replay.mode,
+            ),
+            timestep: FixedTimestep::default(),
+            mode: Mode::Replay(ReplayViewer::new(replay)),
+            screen: Screen::Playing,
+            camera: CameraMode::default(),
+            cameras: vec![],
+            aim: None,
+            input_map: InputMap::default(),
+            rebinding: None,
+            pointer: PointerSteering::default(),
+            gamepads: Gamepads::default(),
+            setup: Setup::default(),
+            restarts: 0,
+            scores: HighScores::default(),
+            records: vec![],
+            notice: None,
+            #[cfg(feature = "net")]
+            join: None,
+            debug: DebugPanel::default(),
+            overlay: PartitionOverlay::default(),
+            #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+            config_watcher: config_watcher::ConfigWatcher::new(CONFIG_PATH),
+        }
+    }
+    /// Join a server, `url` is like `ws://127.0.0.1:7878`.
+    #[cfg(feature = "net")]
+    pub fn with_server(url: String) -> Self {
+        Self {
+            join: Some(url),
+            ..Self::new()
+        }
+    }
+    #[cfg(feature = "net")]
+    fn join(&mut self, ctx: &egui::CtxRef, url: &str) {
+        match crate::net::Client::connect(url) {
+            Ok(client) => {
+                self.notify(ctx, format!("Joining {}", url));
+                self.mode = Mode::Online(Box::new(client));
+                self.screen = Screen::Playing;
+                self.reframe();
+            }
+            Err(err) => self.notify(ctx, format!("Can't join {}: {}", url, err)),
+        }
+    }
+    /// Fresh recorded game with the chosen setup, the config carries over.
+    fn start_game(&mut self) {
+        let Setup {
+            players,
+            rivals,
+            mode,
+        } = self.setup;
+        let config = self.game.state.config().clone();
+        let bots = match rivals {
+            Some(difficulty) => vec![difficulty; RIVALS],
+            None => vec![],
+        };
+        self.game = Game::new(rand::random(), config, players, &bots, mode);
+        let mut recording = Replay::new(self.game.state.seed(), players);
+        recording.bots = bots;
+        recording.mode = mode;
+        self.mode = Mode::Play(Some(recording));
+        self.screen = Screen::Playing;
+        self.restarts = 0;
+        self.reframe();
+    }
+    /// Loaded games are continued from the title, new ones start like them.
+    #[cfg(feature = "persistence")]
+    fn continue_game(&mut self, game: Game) {
+        self.setup = Setup {
+            players: game.players.len(),
+            rivals: game.bots.first().map(|&(_, difficulty)| difficulty),
+            mode: game.state.mode(),
+        };
+        self.restarts = game.state.restarts();
+        self.game = game;
+        self.mode = Mode::Play(None);
+        self.reframe();
+    }
+    /// Ctrl+B cycles through no rivals and rivals of every difficulty.
+    fn rivals_requested(ctx: &egui::CtxRef) -> bool {
+        let input = ctx.input();
+        input.modifiers.command && input.key_pressed(egui::Key::B)
+    }
+    fn next_rivals(&self) -> Option<Difficulty> {
+        match self.setup.rivals {
+            None => Some(Difficulty::ALL[0]),
+            Some(difficulty) => {
+                let i = Difficulty::ALL.iter().position(|&d| d == difficulty);
+                i.and_then(|i| Difficulty::ALL.get(i + 1)).copied()
+            }
+        }
+    }
+    /// Ctrl+G cycles through the game modes.
+    fn game_mode_requested(ctx: &egui::CtxRef) -> bool {
+        let input = ctx.input();
+        input.modifiers.command && input.key_pressed(egui::Key::G)
+    }
+    fn next_game_mode(&self) -> GameMode {
+        let i = GameMode::ALL
+            .iter()
+            .position(|&mode| mode == self.setup.mode);
+        GameMode::ALL[i.map_or(0, |i| (i + 1) % GameMode::ALL.len())]
+    }
+    /// Ctrl+1 to Ctrl+4 start a game with that many players.
+    fn new_game_requested(ctx: &egui::CtxRef) -> Option<usize> {
+        use egui::Key;
+        let input = ctx.input();
+        if !input.modifiers.command {
+            return None;
+        }
+        [Key::Num1, Key::Num2, Key::Num3, Key::Num4]
+            .iter()
+            .position(|&key| input.key_pressed(key))
+            .map(|i| i + 1)
+    }
+    fn notify(&mut self, ctx: &egui::CtxRef, text: String) {
+        self.notice = Some((text, ctx.input().time + 3.0));
+    }
+    /// Designers can tweak `config.ron` while the game is running.
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    fn watch_config(&mut self, ctx: &egui::CtxRef) {
+        let time = ctx.input().time;
+        let text = match self.config_watcher.poll(time) {
+            Some(Ok(config)) => {
+                *self.game.state.config_mut() = config;
+                format!("Config loaded from {}", CONFIG_PATH)
+            }
+            Some(Err(err)) => format!("Failed to load config: {}", err),
+            None => return,
+        };
+        self.notify(ctx, text);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn shortcuts(&mut self, ctx: &egui::CtxRef) {
+        let (save_recording, save_game, load_game) = {
+            let input = ctx.input();
+            let command = input.modifiers.command;
+            (
+                command && input.key_pressed(egui::Key::R),
+                command && input.key_pressed(egui::Key::S),
+                command && input.key_pressed(egui::Key::O),
+            )
+        };
+        if save_recording {
+            self.save_recording(ctx);
+        }
+        #[cfg(feature = "persistence")]
+        {
+            if save_game {
+                self.save_game(ctx);
+            }
+            if load_game {
+                self.load_game(ctx);
+            }
+        }
+        #[cfg(not(feature = "persistence"))]
+        let _ = (save_game, load_game);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_recording(&mut self, ctx: &egui::CtxRef) {
+        let recording = match &self.mode {
+            Mode::Play(Some(recording)) => recording,
+            Mode::Play(None) => {
+                self.notify(ctx, "Loaded game isn't recorded".to_owned());
+                return;
+            }
+            Mode::Replay(_) => return,
+            #[cfg(feature = "net")]
+            Mode::Online(_) => {
+                self.notify(ctx, "Online game isn't recorded".to_owned());
+                return;
+            }
+        };
+        let path = format!("replay-{}-{}.cocr", recording.seed, recording.len());
+        let result = std::fs::File::create(&path)
+            .and_then(|file| recording.write_to(std::io::BufWriter::new(file)));
+        let text = match result {
+            Ok(()) => format!("Replay saved to {}", path),
+            Err(err) => format!("Failed to save replay: {}", err),
+        };
+        self.notify(ctx, text);
+    }
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    fn save_game(&mut self, ctx: &egui::CtxRef) {
+        if !matches!(self.mode, Mode::Play(_)) {
+            return;
+        }
+        let result = ron::ser::to_string(&self.game.save())
+            .map_err(|err| err.to_string())
+            .and_then(|saved| std::fs::write(SAVE_PATH, saved).map_err(|err| err.to_string()));
+        let text = match result {
+            Ok(()) => format!("Game saved to {}", SAVE_PATH),
+            Err(err) => format!("Failed to save game: {}", err),
+        };
+        self.notify(ctx, text);
+    }
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    fn load_game(&mut self, ctx: &egui::CtxRef) {
+        let result = std::fs::read_to_string(SAVE_PATH)
+            .map_err(|err| err.to_string())
+            .and_then(|saved| ron::from_str::<SavedGame>(&saved).map_err(|err| err.to_string()))
+            .and_then(|saved| Game::load(saved).ok_or_else(|| "player is missing".to_owned()));
+        let text = match result {
+            Ok(game) => {
+                self.continue_game(game);
+                self.screen = Screen::Playing;
+                format!("Game loaded from {}", SAVE_PATH)
+            }
+            Err(err) => format!("Failed to load game: {}", err),
+        };
+        self.notify(ctx, text);
+    }
+    /// Command of every player, a gamepad takes over from the keys while its stick is moved.
+    /// The only player can also steer with the pointer.
+    /// The ability key belongs to the first player, every gamepad has its own button.
+    fn commands(
+        &mut self,
+        ctx: &egui::CtxRef,
+        players: usize,
+        pads: &[PadInput],
+    ) -> Vec<PlayerCommand> {
+        let map = &self.input_map;
+        let mut commands: Vec<PlayerCommand> = (0..players)
+            .map(|player| {
+                let pad = pads.get(player);
+                let steer = match pad {
+                    Some(pad) if pad.dir != DVec2::ZERO => pad.dir,
+                    _ => map.steer(ctx.input(), player, players),
+                };
+                let key = player == 0 && map.held(ctx.input(), Control::Ability);
+                let button = pad.is_some_and(|pad| pad.held.contains(&map.pad_ability));
+                PlayerCommand {
+                    steer,
+                    ability: key || button,
+                }
+            })
+            .collect();
+        if players != 1 || self.overlay.is_open() {
+            return commands;
+        }
+        if commands[0].steer != DVec2::ZERO {
+            self.pointer.reset();
+            return commands;
+        }
+        let mode = map.pointer;
+        if let Some(dir) = self
+            .aim
+            .and_then(|(view, core)| self.pointer.input(ctx, view, core, mode))
+        {
+            commands[0].steer = dir;
+        }
+        commands
+    }
+}
+
+impl epi::App for App {
+    fn name(&self) -> &str {
+        "Core of Color"
+    }
+
+    #[cfg(feature = "persistence")]
+    fn setup(
+        &mut self,
+        _ctx: &egui::CtxRef,
+        _frame: &mut epi::Frame<'_>,
+        storage: Option<&dyn epi::Storage>,
+    ) {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(scores) = storage.and_then(|storage| epi::get_value(storage, SCORES_KEY)) {
+            self.scores = scores;
+        }
+        if let Some(input_map) = storage.and_then(|storage| epi::get_value(storage, INPUT_KEY)) {
+            self.input_map = input_map;
+        }
+        if !matches!(self.mode, Mode::Play(_)) {
+            return;
+        }
+        let saved = storage.and_then(|storage| epi::get_value(storage, GAME_KEY));
+        if let Some(game) = saved.and_then(Game::load) {
+            self.continue_game(game);
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save(&mut self, storage: &mut dyn epi::Storage) {
+        #[cfg(target_arch = "wasm32")]
+        epi::set_value(storage, SCORES_KEY, &self.scores);
+        epi::set_value(storage, INPUT_KEY, &self.input_map);
+        if let Mode::Play(_) = self.mode {
+            epi::set_value(storage, GAME_KEY, &self.game.save());
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::CtxRef, frame: &mut epi::Frame<'_>) {
+        #[cfg(feature = "net")]
+        if let Some(url) = self.join.take() {
+            self.join(ctx, &url);
+        }
+        let steps = self.timestep.advance(ctx.input().time);
+        let running = self.screen == Screen::Playing;
+        let players = if self.is_local() {
+            self.game.players.len()
+        } else {
+            1
+        };
+        let pads = self.gamepads.poll(self.input_map.pad_dead_zone);
+        let pad_pause = Some(self.input_map.pad_pause);
+        if self.rebinding.is_none() && pads.iter().any(|pad| pad.pressed == pad_pause) {
+            match self.screen {
+                Screen::Playing => self.screen = Screen::Paused,
+                Screen::Paused => self.screen = Screen::Playing,
+                _ => {}
+            }
+        }
+        let commands = if running {
+            self.commands(ctx, players, &pads)
+        } else {
+            vec![PlayerCommand::default(); players]
+        };
+        let mut round_over = false;
+        let scene = match &mut self.mode {
+            Mode::Play(recording) if running => {
+                for _ in 0..steps {
+                    if let Some(recording) = recording {
+                        let inputs: Vec<_> =
+                            commands.iter().map(|&command| command.into()).collect();
+                        recording.push(self.game.state.config(), &inputs);
+                    }
+                    self.game.step(&commands);
+                    let state = &self.game.state;
+                    if state.results().is_some() || state.restarts() != self.restarts {
+                        self.restarts = state.restarts();
+                        self.screen = Screen::RoundOver;
+                        round_over = true;
+                        break;
+                    }
+                }
+                Scene::local(&self.game, self.timestep.alpha())
+            }
+            // World stands still behind the menus.
+            Mode::Play(_) => Scene::local(&self.game, 1.0),
+            Mode::Replay(viewer) => {
+                let steps = if running { steps } else { 0 };
+                let alpha = if viewer.update(ctx, &mut self.game, steps) {
+                    self.timestep.alpha()
+                } else {
+                    1.0
+                };
+                Scene::local(&self.game, alpha)
+            }
+            // Server doesn't wait, the connection is kept up behind the menus too.
+            #[cfg(feature = "net")]
+            Mode::Online(client) => {
+                let time = ctx.input().time;
+                match client.update(time, commands[0].steer, commands[0].ability) {
+                    Ok(()) => Scene::online(client, time),
+                    Err(err) => {
+                        let text = format!("Left {}: {}", client.url(), err);
+                        self.mode = Mode::Play(None);
+                        self.screen = Screen::Title;
+                        self.reframe();
+                        self.notify(ctx, text);
+                        Scene::local(&self.game, 1.0)
+                    }
+                }
+            }
+        };
+        if round_over {
+            self.record_scores(ctx);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.shortcuts(ctx);
+        #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+        self.watch_config(ctx);
+        if let Some((text, until)) = &self.notice {
+            if ctx.input().time < *until {
+                egui::Area::new("notice")
+                    .anchor(egui::Align2::LEFT_TOP, [10.0, 10.0])
+                    .show(ctx, |ui| ui.label(text));
+            } else {
+                self.notice = None;
+            }
+        }
+        // Key being bound on the controls screen doesn't do anything else yet.
+        let pressed =
+            |control| self.rebinding.is_none() && self.input_map.pressed(ctx.input(), control);
+        let [debug, overlay, camera] =
+            [Control::DebugPanel, Control::Overlay, Control::Camera].map(pressed);
+        if debug {
+            self.debug.toggle();
+        }
+        if self.is_local() {
+            self.debug.show(ctx, &mut self.game);
+        }
+        if overlay && self.is_local() {
+            self.overlay.toggle();
+        }
+        if let Some(players) = Self::new_game_requested(ctx) {
+            self.setup.players = players;
+            self.start_game();
+        }
+        if Self::rivals_requested(ctx) {
+            self.setup.rivals = self.next_rivals();
+            let text = match self.setup.rivals {
+                Some(difficulty) => format!("{} {} AI rivals", RIVALS, difficulty.name()),
+                None => "No AI rivals".to_owned(),
+            };
+            self.notify(ctx, text);
+            self.start_game();
+        }
+        if Self::game_mode_requested(ctx) {
+            self.setup.mode = self.next_game_mode();
+            self.notify(ctx, format!("{} mode", self.setup.mode.name()));
+            self.start_game();
+        }
+        if camera {
+            self.camera = self.camera.toggled();
+        }
+        if self.screen.shows_game() {
+            Self::scoreboard(ctx, &scene);
+            hud::minimap(ctx, &scene);
+            if self.is_local() {
+                round::status(ctx, &self.game, self.timestep.dt());
+                hud::stats(ctx, &self.game, self.timestep.dt());
+            }
+        }
+        let pad_pressed = pads.iter().find_map(|pad| pad.pressed);
+        if let Some(action) = self.screen(ctx, frame.is_web(), pad_pressed) {
+            self.act(action, frame);
+        }
+        self.draw(ctx, &scene);
+        ctx.request_repaint();
+    }
+}
+
+impl App {
+    /// Menu of the current screen, the pause control pauses the game.
+    /// `pad_pressed` is a gamepad button pressed this frame, for rebinding.
+    fn screen(
+        &mut self,
+        ctx: &egui::CtxRef,
+        web: bool,
+        pad_pressed: Option<PadButton>,
+    ) -> Option<Action> {
+        let can_quit = !web;
+        match self.screen {
+            Screen::Title => {
+                let can_continue =
+                    matches!(self.mode, Mode::Play(_)) && self.game.state.results().is_none();
+                screen::title(ctx, can_continue, can_quit)
+            }
+            Screen::ModeSelect => screen::mode_select(ctx, &self.input_map, &mut self.setup),
+            Screen::Settings { paused } => screen::settings(
+                ctx,
+                paused,
+                &mut self.camera,
+                &self.input_map,
+                &mut self.debug,
+                self.game.state.config_mut(),
+            ),
+            Screen::Controls { paused } => screen::controls(
+                ctx,
+                paused,
+                &mut self.input_map,
+                &mut self.rebinding,
+                pad_pressed,
+            ),
+            Screen::Playing => self
+                .input_map
+                .pressed(ctx.input(), Control::Pause)
+                .then_some(Action::Show(Screen::Paused)),
+            Screen::Paused => screen::paused(ctx, &self.input_map, !self.is_local(), can_quit),
+            Screen::RoundOver => screen::round_over(
+                ctx,
+                &self.input_map,
+                &self.game,
+                &self.records,
+                self.timestep.dt(),
+            ),
+            Screen::HighScores { mode } => {
+                screen::high_scores(ctx, &self.input_map, &self.scores, mode, self.timestep.dt())
+            }
+        }
+    }
+    fn act(&mut self, action: Action, frame: &mut epi::Frame<'_>) {
+        match action {
+            Action::Show(screen) => self.screen = screen,
+            Action::Start => self.start_game(),
+            Action::Leave => {
+                self.mode = Mode::Play(None);
+                self.screen = Screen::Title;
+                self.reframe();
+            }
+            Action::Quit => frame.quit(),
+        }
+    }
+    /// Local players of the round that just ended go into the high scores.
+    #[cfg_attr(
+        not(all(feature = "persistence", not(target_arch = "wasm32"))),
+        allow(unused_variables)
+    )]
+    fn record_scores(&mut self, ctx: &egui::CtxRef) {
+        let Self {
+            game,
+            setup,
+            scores,
+            records,
+            ..
+        } = self;
+        records.clear();
+        let state = &game.state;
+        let results = match state.results().or_else(|| state.drained()) {
+            Some(results) => results,
+            None => return,
+        };
+        for &core in &game.players {
+            let standing = results.standings.iter().find(|s| s.core == core);
+            let score = standing.and_then(|s| scores::HighScore::new(results, s, setup));
+            if let Some(place) = score.and_then(|score| scores.add(score)) {
+                records.push((game.label(core), place));
+            }
+        }
+        #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+        if !self.records.is_empty() {
+            if let Err(err) = self.scores.save(SCORES_PATH) {
+                self.notify(ctx, format!("Failed to save high scores: {}", err));
+            }
+        }
+    }
+    /// Another world is on screen, cameras jump to it and the tapped point is dropped.
+    fn reframe(&mut self) {
+        self.cameras.clear();
+        self.pointer.reset();
+    }
+    /// Simulation runs here, not on a server.
+    fn is_local(&self) -> bool {
+        match self.mode {
+            Mode::Play(_) | Mode::Replay(_) => true,
+            #[cfg(feature = "net")]
+            Mode::Online(_) => false,
+        }
+    }
+    /// Size and orbiting dots of every player, only shown when there is more than one.
+    fn scoreboard(ctx: &egui::CtxRef, scene: &Scene) {
+        if scene.players.len() <= 1 {
+            return;
+        }
+        egui::Area::new("scoreboard")
+            .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+            .show(ctx, |ui| {
+                for player in &scene.players {
+                    let you = if player.you { " (you)" } else { "" };
+                    let text = match &player.core {
+                        Some(core) => format!(
+                            "{}{}  size {:.2}  dots {}",
+                            player.label, you, core.size, core.orbiting_dots
+                        ),
+                        None => format!("{}{}  out", player.label, you),
+                    };
+                    ui.label(text);
+                }
+            });
+    }
+
+    fn draw(&mut self, ctx: &egui::CtxRef, scene: &Scene) {
+        let state = Some(&self.game.state).filter(|_| self.is_local());
+        let overlay = &mut self.overlay;
+        let camera = self.camera;
+        let cameras = &mut self.cameras;
+        let aim = &mut self.aim;
+        let input_map = &self.input_map;
+        let dt = ctx.input().unstable_dt.min(0.1) as f64;
+        let tick = scene.tick;
+        let labels = scene.players.len() > 1;
+
+        egui::CentralPanel::default()
+            .frame(Frame::dark_canvas(&ctx.style()))
+            .show(ctx, |ui| {
+                let shots = camera.shots(ui.max_rect(), &scene.followed);
+                if cameras.len() != shots.len() {
+                    *cameras = vec![Camera::default(); shots.len()];
+                }
+                let input = ui.input();
+                let zoom = input.zoom_delta() as f64
+                    * (input.scroll_delta.y as f64 * ZOOM_PER_SCROLL).exp();
+                // Zoom keys zoom every view, the wheel and pinch only the one under the pointer.
+                let key_zoom = input_map.zoom(input, dt);
+                let views: Vec<_> = cameras
+                    .iter_mut()
+                    .zip(shots)
+                    .map(|(camera, (rect, shot))| {
+                        if zoom != 1.0 && ui.rect_contains_pointer(rect) {
+                            camera.zoom_by(zoom);
+                        }
+                        camera.zoom_by(key_zoom);
+                        camera.follow(shot, dt);
+                        camera.view(rect)
+                    })
+                    .collect();
+                *aim = match (views.as_slice(), scene.followed.as_slice()) {
+                    ([view], [Some(focus)]) => Some((*view, focus.pos)),
+                    _ => None,
+                };
+                if let Some(state) = state.filter(|_| overlay.is_open()) {
+                    let input = ui.input();
+                    let clicked = input.pointer.any_click() && !ctx.is_using_pointer();
+                    if let Some(pos) = input.pointer.interact_pos().filter(|_| clicked) {
+                        if let Some(view) = views.iter().find(|view| view.rect.contains(pos)) {
+                            overlay.select(state, view.to_world(pos));
+                        }
+                    }
+                }
+                let cores = scene
+                    .players
+                    .iter()
+                    .filter_map(|player| player.core.as_ref());
+                for view in &views {
+                    let painter = ui.painter().sub_region(view.rect);
+                    let transform = |pos| view.to_screen(pos);
+                    let scale = view.scale();
+                    let mut shapes = vec![];
+                    for core in cores.clone() {
+                        shapes.push(egui::Shape::circle_stroke(
+                            transform(core.pos),
+                            (scale * core.size.powf(0.5)) as f32,
+                            (1.0, Color32::from_rgba_premultiplied(10, 10, 10, 10)),
+                        ));
+                        let mut misc_dots = core.size.floor();
+                        while misc_dots >= 1.0 {
+                            let frac = std::f64::consts::TAU / 3.0;
+                            let transform = |vec| transform(core.pos + vec);
+                            shapes.push(misc_dot(misc_dots, tick, transform));
+                            shapes.push(misc_dot(misc_dots, tick + frac, transform));
+                            shapes.push(misc_dot(misc_dots, tick + frac * 2.0, transform));
+                            misc_dots -= 1.0;
+                        }
+                    }
+                    shapes.push(egui::Shape::mesh(dots::mesh(*view, &scene.dots)));
+                    if let Some(state) = state {
+                        shapes.extend(overlay.shapes(state, transform));
+                    }
+                    if views.len() > 1 {
+                        shapes.push(egui::Shape::rect_stroke(
+                            view.rect,
+                            0.0,
+                            (1.0, Color32::from_gray(60)),
+                        ));
+                    }
+                    painter.extend(shapes);
+                    if labels {
+                        for player in &scene.players {
+                            let core = match &player.core {
+                                Some(core) => core,
+                                None => continue,
+                            };
+                            let top = core.pos + DVec2::new(0.0, core.size.powf(0.5) + 0.3);
+                            painter.text(
+                                transform(top),
+                                egui::Align2::CENTER_BOTTOM,
+                                &player.label,
+                                egui::TextStyle::Small,
+                                Color32::from_gray(160),
+                            );
+                        }
+                    }
+                }
+            });
+    }
+}
+
+fn misc_dot(misc_dots: f64, tick: f64, transform: impl Fn(DVec2) -> egui::Pos2) -> egui::Shape {
+    let pos = DVec2::new(
+        (-tick + misc_dots * 0.5).cos() * misc_dots * 0.5,
+        (-tick + misc_dots * 0.5).sin() * misc_dots * 0.25,
+    );
+    let center = transform(pos * 0.5);
+    let color = Color32::WHITE;
+    egui::Shape::circle_filled(center, 1.0, color)
+}
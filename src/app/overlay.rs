@@ -0,0 +1,62 @@
+use crate::state::State;
+use eframe::egui::{self, Color32, Shape, Stroke};
+use glam::DVec2;
+use hecs::Entity;
+
+/// Draws the cells of the spatial index, and for the selected dot
+/// the cells it searches and lines to every neighbour found.
+#[derive(Default)]
+pub struct PartitionOverlay {
+    open: bool,
+    selected: Option<Entity>,
+}
+
+impl PartitionOverlay {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.selected = None;
+    }
+    /// While open, pointer selects dots instead of steering.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    pub fn select(&mut self, state: &State, pos: DVec2) {
+        self.selected = state.nearest(pos);
+    }
+    pub fn shapes(&self, state: &State, transform: impl Fn(DVec2) -> egui::Pos2) -> Vec<Shape> {
+        if !self.open {
+            return vec![];
+        }
+        let selected = self
+            .selected
+            .and_then(|entity| Some((entity, state.position(entity)?.vec)));
+        let radius = state.tick_stats().neighbour_radius;
+
+        let mut shapes = vec![];
+        for bounds in state.partition() {
+            let searched = selected.is_some_and(|(_, pos)| bounds.distance(pos) <= radius);
+            let stroke = if searched {
+                Stroke::new(1.0, Color32::YELLOW)
+            } else {
+                Stroke::new(0.5, Color32::from_gray(80))
+            };
+            let rect = egui::Rect::from_two_pos(transform(bounds.min), transform(bounds.max));
+            shapes.push(Shape::rect_stroke(rect, 0.0, stroke));
+        }
+        if let Some((entity, pos)) = selected {
+            let center = transform(pos);
+            let edge = transform(pos + DVec2::new(radius, 0.0));
+            let stroke = Stroke::new(1.0, Color32::LIGHT_BLUE);
+            shapes.push(Shape::circle_stroke(center, edge.x - center.x, stroke));
+            let stroke = Stroke::new(0.5, Color32::from_rgba_unmultiplied(255, 255, 255, 60));
+            for offset in state.neighbour_offsets(entity) {
+                shapes.push(Shape::line_segment(
+                    [center, transform(pos + offset)],
+                    stroke,
+                ));
+            }
+            shapes.push(Shape::circle_stroke(center, 6.0, (1.5, Color32::RED)));
+        }
+        shapes
+    }
+}
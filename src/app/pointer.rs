@@ -0,0 +1,70 @@
+use super::view::View;
+use eframe::egui;
+use glam::DVec2;
+
+/// World units around the core where the pointer doesn't steer, a tap target within it is reached.
+const DEAD_ZONE: f64 = 0.5;
+/// World units from the core where the pointer steers at full strength.
+const FULL_STRENGTH: f64 = 5.0;
+
+/// How the pointer steers the only local core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerMode {
+    /// Core heads for the pointer while it is held down.
+    #[default]
+    Hold,
+    /// Core also heads for the last tapped point until it gets there, handy on touch screens.
+    Tap,
+}
+
+/// Steering of one core by the pointer, in world space.
+#[derive(Debug, Default)]
+pub struct PointerSteering {
+    target: Option<DVec2>,
+}
+
+impl PointerSteering {
+    /// `view` shows the `core`, `None` when the pointer isn't steering.
+    pub fn input(
+        &mut self,
+        ctx: &egui::CtxRef,
+        view: View,
+        core: DVec2,
+        mode: PointerMode,
+    ) -> Option<DVec2> {
+        let input = ctx.input();
+        // Asked before the world is drawn, so only the HUD and the menus count as areas.
+        let over_hud = ctx.is_using_pointer() || ctx.is_pointer_over_area();
+        let pos = input
+            .pointer
+            .interact_pos()
+            .filter(|&pos| view.rect.contains(pos) && !over_hud);
+        if let Some(pos) = pos {
+            let target = view.to_world(pos);
+            if input.pointer.any_down() {
+                self.target = None;
+                return Some(strength(target - core));
+            }
+            if mode == PointerMode::Tap && input.pointer.any_click() {
+                self.target = Some(target);
+            }
+        }
+        let offset = self.target? - core;
+        if offset.length() <= DEAD_ZONE {
+            self.target = None;
+        }
+        Some(strength(offset))
+    }
+    /// Forgets the tapped point, like when the world changes under it.
+    pub fn reset(&mut self) {
+        self.target = None;
+    }
+}
+
+/// Direction toward `offset`, from nothing at the dead zone up to a unit vector.
+fn strength(offset: DVec2) -> DVec2 {
+    let length = offset.length();
+    let strength = ((length - DEAD_ZONE) / (FULL_STRENGTH - DEAD_ZONE)).clamp(0.0, 1.0);
+    offset.normalize_or_zero() * strength
+}
@@ -0,0 +1,80 @@
+use super::Game;
+use crate::state::{GameMode, Results, Standing};
+use eframe::egui::{self, Align2};
+
+/// Mode and its clock at the top of the screen, nothing in Zen.
+pub fn status(ctx: &egui::CtxRef, game: &Game, dt: f64) {
+    let state = &game.state;
+    let ticks = state.round_ticks();
+    let text = match state.mode() {
+        GameMode::Zen => return,
+        GameMode::TimeAttack => {
+            let limit = state.config().round.time_attack_ticks;
+            format!("{} left", clock(limit.saturating_sub(ticks), dt))
+        }
+        GameMode::Survival | GameMode::BlackholeRace => clock(ticks, dt),
+    };
+    egui::Area::new("round")
+        .anchor(Align2::CENTER_TOP, [0.0, 10.0])
+        .show(ctx, |ui| {
+            ui.label(format!("{}  {}", state.mode().name(), text));
+        });
+}
+
+/// Who won, how long it took and how every core did.
+pub fn summary(ui: &mut egui::Ui, game: &Game, results: &Results, dt: f64) {
+    ui.heading(headline(game, results));
+    ui.label(format!("Round took {}", clock(results.ticks, dt)));
+    ui.separator();
+    egui::Grid::new("standings").show(ui, |ui| {
+        for (place, standing) in results.standings.iter().enumerate() {
+            ui.label(format!("{}.", place + 1));
+            ui.label(game.label(standing.core));
+            ui.label(score(results.mode, standing, results.ticks, dt));
+            ui.end_row();
+        }
+    });
+}
+
+fn headline(game: &Game, results: &Results) -> String {
+    match results.winner {
+        _ if results.mode == GameMode::Zen => "The world is drained".to_owned(),
+        Some(winner) if game.players.len() == 1 => {
+            if winner == game.players[0] {
+                "You won".to_owned()
+            } else {
+                format!("You lost, {} won", game.label(winner))
+            }
+        }
+        Some(winner) => format!("{} won", game.label(winner)),
+        None if game.players.len() == 1 => "You lost".to_owned(),
+        None => "Nobody made it".to_owned(),
+    }
+}
+
+/// What the mode ranks cores by.
+fn score(mode: GameMode, standing: &Standing, ticks: u32, dt: f64) -> String {
+    let energy = format!("energy {:.2}", standing.energy);
+    let out = if standing.out_at.is_some() {
+        ", out"
+    } else {
+        ""
+    };
+    match mode {
+        GameMode::Survival => {
+            let survived = standing.out_at.unwrap_or(ticks);
+            format!("survived {}{}", clock(survived, dt), out)
+        }
+        GameMode::BlackholeRace => match standing.blackhole_at {
+            Some(at) => format!("blackhole in {}", clock(at, dt)),
+            None => format!("{}{}", energy, out),
+        },
+        GameMode::Zen | GameMode::TimeAttack => format!("{}{}", energy, out),
+    }
+}
+
+/// `ticks` as minutes and seconds.
+pub fn clock(ticks: u32, dt: f64) -> String {
+    let seconds = (ticks as f64 * dt) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,138 @@
+use super::{view::Focus, Game};
+use crate::state::State;
+use eframe::egui::Color32;
+use glam::DVec2;
+
+/// What a frame shows, taken from the local game or from snapshots of a server.
+pub struct Scene {
+    pub players: Vec<ScenePlayer>,
+    pub dots: Vec<SceneDot>,
+    /// Cores the camera keeps in view, one per view of a split screen.
+    pub followed: Vec<Option<Focus>>,
+    /// Phase of the core animation.
+    pub tick: f64,
+}
+
+pub struct ScenePlayer {
+    /// `P1`, `P2` and so on for players, `AI 1` and so on for AI rivals.
+    pub label: String,
+    /// Our own core online.
+    pub you: bool,
+    /// `None` when the player has no core.
+    pub core: Option<Core>,
+}
+
+/// Core of a player as drawn this frame.
+pub struct Core {
+    pub pos: DVec2,
+    pub size: f64,
+    pub orbiting_dots: usize,
+}
+
+impl Core {
+    pub fn focus(&self) -> Focus {
+        Focus {
+            pos: self.pos,
+            size: self.size,
+            orbiting_dots: self.orbiting_dots,
+        }
+    }
+    fn of(state: &State, player: hecs::Entity, alpha: f64) -> Option<Self> {
+        Some(Self {
+            pos: state.interpolated_position(player, alpha)?.vec,
+            size: state.size(player)?.0,
+            orbiting_dots: state.player_stats(player)?.orbiting_dots,
+        })
+    }
+}
+
+pub struct SceneDot {
+    pub pos: DVec2,
+    pub color: Color32,
+    pub size: f32,
+    pub hollow: bool,
+}
+
+impl Scene {
+    pub fn local(game: &Game, alpha: f64) -> Self {
+        let state = &game.state;
+        let mut players: Vec<_> = game
+            .players
+            .iter()
+            .map(|&player| ScenePlayer {
+                label: game.label(player),
+                you: false,
+                core: Core::of(state, player, alpha),
+            })
+            .collect();
+        let followed = players
+            .iter()
+            .map(|player| Some(player.core.as_ref()?.focus()))
+            .collect();
+        players.extend(game.bots.iter().map(|&(bot, _)| ScenePlayer {
+            label: game.label(bot),
+            you: false,
+            core: Core::of(state, bot, alpha),
+        }));
+        let dots = state
+            .dots(alpha)
+            .iter()
+            .map(|dot| SceneDot {
+                pos: dot.pos.vec,
+                color: Color32::from(&dot.color),
+                size: dot.size,
+                hollow: dot.hollow,
+            })
+            .collect();
+        Self {
+            players,
+            dots,
+            followed,
+            tick: game.tick + 0.1 * alpha,
+        }
+    }
+
+    /// The camera follows our core only.
+    #[cfg(feature = "net")]
+    pub fn online(client: &crate::net::Client, time: f64) -> Self {
+        let alpha = client.alpha(time);
+        let you = client.slot();
+        let players: Vec<_> = client
+            .cores(alpha)
+            .map(|core| ScenePlayer {
+                label: format!("P{}", core.slot + 1),
+                you: Some(core.slot) == you,
+                core: Some(Core {
+                    pos: core.pos,
+                    size: core.size,
+                    orbiting_dots: core.orbiting_dots,
+                }),
+            })
+            .collect();
+        let followed = players
+            .iter()
+            .find(|player| player.you)
+            .and_then(|player| Some(player.core.as_ref()?.focus()));
+        let dots = client
+            .dots(alpha)
+            .map(|dot| SceneDot {
+                pos: dot.pos,
+                color: hsl_color(dot.hue, dot.saturation, dot.lightness),
+                size: dot.size,
+                hollow: dot.hollow,
+            })
+            .collect();
+        Self {
+            players,
+            dots,
+            followed: vec![followed],
+            tick: client.tick(alpha) * 0.1,
+        }
+    }
+}
+
+pub fn hsl_color(hue: f64, saturation: f64, lightness: f64) -> Color32 {
+    use colorsys::{Hsl, Rgb};
+    let rgb: [u8; 3] = Rgb::from(&Hsl::new(hue, saturation, lightness, None)).into();
+    Color32::from_rgb(rgb[0], rgb[1], rgb[2])
+}
@@ -0,0 +1,186 @@
+use super::screen::Setup;
+use crate::state::{Difficulty, GameMode, Results, Standing};
+use float_ord::FloatOrd;
+use std::cmp::Reverse;
+
+/// Rounds kept for every mode.
+pub const KEPT: usize = 10;
+
+/// Best local rounds of every mode.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighScores(Vec<HighScore>);
+
+/// How one player did in a round.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighScore {
+    pub mode: GameMode,
+    /// What the mode ranks by: energy gained in Zen, energy in Time Attack,
+    /// ticks survived in Survival and ticks to the blackhole in a race.
+    pub value: f64,
+    pub players: usize,
+    pub rivals: Option<Difficulty>,
+    /// Length of the round.
+    pub ticks: u32,
+    pub energy: f64,
+    pub dots_drained: u32,
+    pub peak_size: f64,
+}
+
+impl HighScore {
+    /// `None` for a race the player didn't finish.
+    pub fn new(results: &Results, standing: &Standing, setup: &Setup) -> Option<Self> {
+        let value = match results.mode {
+            GameMode::Zen => standing.score.energy_gained,
+            GameMode::TimeAttack => standing.energy,
+            GameMode::Survival => standing.out_at.unwrap_or(results.ticks) as f64,
+            GameMode::BlackholeRace => standing.blackhole_at? as f64,
+        };
+        Some(Self {
+            mode: results.mode,
+            value,
+            players: setup.players,
+            rivals: setup.rivals,
+            ticks: results.ticks,
+            energy: standing.energy,
+            dots_drained: standing.score.dots_drained,
+            peak_size: standing.score.peak_size,
+        })
+    }
+    /// Higher is better, a race is won by the fastest blackhole.
+    fn rank(&self) -> f64 {
+        match self.mode {
+            GameMode::BlackholeRace => -self.value,
+            _ => self.value,
+        }
+    }
+}
+
+impl HighScores {
+    /// Best first.
+    pub fn table(&self, mode: GameMode) -> impl Iterator<Item = &HighScore> {
+        self.0.iter().filter(move |score| score.mode == mode)
+    }
+    /// Place the score took, counted from 0, `None` if it didn't make the table.
+    pub fn add(&mut self, score: HighScore) -> Option<usize> {
+        let place = self
+            .table(score.mode)
+            .take_while(|other| other.rank() >= score.rank())
+            .count();
+        if place >= KEPT {
+            return None;
+        }
+        let mode = score.mode;
+        self.0.push(score);
+        self.0.sort_by_key(|score| {
+            let mode = GameMode::ALL.iter().position(|&mode| mode == score.mode);
+            (mode, Reverse(FloatOrd(score.rank())))
+        });
+        let mut kept = 0;
+        self.0.retain(|score| {
+            if score.mode != mode {
+                return true;
+            }
+            kept += 1;
+            kept <= KEPT
+        });
+        Some(place)
+    }
+
+    /// Missing file is an empty table.
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    pub fn load(path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(saved) => ron::from_str(&saved).map_err(|err| err.to_string()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+    #[cfg(all(feature = "persistence", not(target_arch = "wasm32")))]
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let saved = ron::ser::to_string(self).map_err(|err| err.to_string())?;
+        std::fs::write(path, saved).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(mode: GameMode, value: f64) -> HighScore {
+        HighScore {
+            mode,
+            value,
+            players: 1,
+            rivals: None,
+            ticks: 100,
+            energy: 0.0,
+            dots_drained: 0,
+            peak_size: 1.0,
+        }
+    }
+    fn values(scores: &HighScores, mode: GameMode) -> Vec<f64> {
+        scores.table(mode).map(|score| score.value).collect()
+    }
+
+    #[test]
+    fn ties_go_below_the_earlier_scores() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.add(score(GameMode::Zen, 5.0)), Some(0));
+        assert_eq!(scores.add(score(GameMode::Zen, 3.0)), Some(1));
+        let mut tie = score(GameMode::Zen, 5.0);
+        tie.dots_drained = 1;
+        assert_eq!(scores.add(tie), Some(1));
+        let table: Vec<_> = scores
+            .table(GameMode::Zen)
+            .map(|score| (score.value, score.dots_drained))
+            .collect();
+        assert_eq!(table, [(5.0, 0), (5.0, 1), (3.0, 0)]);
+    }
+
+    #[test]
+    fn race_is_ranked_by_the_lowest_tick() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.add(score(GameMode::BlackholeRace, 900.0)), Some(0));
+        assert_eq!(scores.add(score(GameMode::BlackholeRace, 300.0)), Some(0));
+        assert_eq!(scores.add(score(GameMode::BlackholeRace, 600.0)), Some(1));
+        assert_eq!(
+            values(&scores, GameMode::BlackholeRace),
+            [300.0, 600.0, 900.0]
+        );
+    }
+
+    #[test]
+    fn every_mode_keeps_its_own_best() {
+        let mut scores = HighScores::default();
+        for &mode in GameMode::ALL.iter() {
+            for value in 0..KEPT {
+                scores.add(score(mode, 100.0 + value as f64));
+            }
+        }
+        for &mode in GameMode::ALL.iter() {
+            let worst = match mode {
+                GameMode::BlackholeRace => 200.0,
+                _ => 0.0,
+            };
+            assert_eq!(scores.add(score(mode, worst)), None);
+            let best = match mode {
+                GameMode::BlackholeRace => 50.0,
+                _ => 500.0,
+            };
+            assert_eq!(scores.add(score(mode, best)), Some(0));
+        }
+        for &mode in GameMode::ALL.iter() {
+            let table = values(&scores, mode);
+            assert_eq!(table.len(), KEPT);
+            // The best stays and the worst of the full table is gone.
+            let (best, dropped) = match mode {
+                GameMode::BlackholeRace => (50.0, 109.0),
+                _ => (500.0, 100.0),
+            };
+            assert_eq!(table[0], best);
+            assert!(!table.contains(&dropped));
+        }
+    }
+}
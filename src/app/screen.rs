@@ -0,0 +1,461 @@
+use super::{
+    controls::{Control, Dir, InputMap, MAX_PLAYERS},
+    debug::DebugPanel,
+    gamepad::PadButton,
+    pointer::PointerMode,
+    round::{self, clock},
+    scores::HighScores,
+    view::CameraMode,
+    Game,
+};
+use crate::state::{Difficulty, GameConfig, GameMode};
+use eframe::egui::{self, Align2, Key};
+
+/// Menus around the game, the local world only runs while `Playing`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Title,
+    ModeSelect,
+    /// Goes back to the pause menu or to the title, whichever it was opened from.
+    Settings {
+        paused: bool,
+    },
+    /// Opened from the settings, goes back to them.
+    Controls {
+        paused: bool,
+    },
+    Playing,
+    Paused,
+    /// Results of a finished round, or a summary of a drained Zen world.
+    RoundOver,
+    /// Table of one mode.
+    HighScores {
+        mode: GameMode,
+    },
+}
+
+impl Screen {
+    /// Menus of the title hide the scoreboard and the clock.
+    pub fn shows_game(self) -> bool {
+        !matches!(
+            self,
+            Screen::Title
+                | Screen::ModeSelect
+                | Screen::Settings { paused: false }
+                | Screen::Controls { paused: false }
+                | Screen::HighScores { .. }
+        )
+    }
+}
+
+/// What a screen asks the app to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Show(Screen),
+    /// New game with the chosen setup.
+    Start,
+    /// Leaves the server for the title.
+    Leave,
+    Quit,
+}
+
+/// Players, rivals and mode of new games.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Setup {
+    pub players: usize,
+    pub rivals: Option<Difficulty>,
+    pub mode: GameMode,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self {
+            players: 1,
+            rivals: None,
+            mode: GameMode::default(),
+        }
+    }
+}
+
+/// `can_continue` when there is a running game to go back to.
+pub fn title(ctx: &egui::CtxRef, can_continue: bool, can_quit: bool) -> Option<Action> {
+    let mut action = None;
+    menu(ctx, "title", |ui| {
+        ui.heading("Core of Color");
+        ui.add_space(10.0);
+        if can_continue && button(ui, "Continue") {
+            action = Some(Action::Show(Screen::Playing));
+        }
+        if button(ui, "New game") {
+            action = Some(Action::Show(Screen::ModeSelect));
+        }
+        if button(ui, "High scores") {
+            let mode = GameMode::default();
+            action = Some(Action::Show(Screen::HighScores { mode }));
+        }
+        if button(ui, "Settings") {
+            action = Some(Action::Show(Screen::Settings { paused: false }));
+        }
+        if can_quit && button(ui, "Quit") {
+            action = Some(Action::Quit);
+        }
+    });
+    action
+}
+
+pub fn mode_select(ctx: &egui::CtxRef, input_map: &InputMap, setup: &mut Setup) -> Option<Action> {
+    let mut action = keys(ctx, input_map, Action::Start, Action::Show(Screen::Title));
+    menu(ctx, "mode select", |ui| {
+        ui.heading("New game");
+        ui.add_space(10.0);
+        for &mode in &GameMode::ALL {
+            ui.radio_value(&mut setup.mode, mode, mode.name());
+            ui.small(goal(mode));
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Players");
+            for players in 1..=4 {
+                ui.selectable_value(&mut setup.players, players, players.to_string());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("AI rivals");
+            ui.selectable_value(&mut setup.rivals, None, "none");
+            for &difficulty in &Difficulty::ALL {
+                ui.selectable_value(&mut setup.rivals, Some(difficulty), difficulty.name());
+            }
+        });
+        ui.separator();
+        if button(ui, "Start (Enter)") {
+            action = Some(Action::Start);
+        }
+        if button(ui, &with_pause(input_map, "Back")) {
+            action = Some(Action::Show(Screen::Title));
+        }
+    });
+    action
+}
+
+fn goal(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::Zen => "Endless, the world starts over once it is drained.",
+        GameMode::TimeAttack => "Most energy in a few minutes.",
+        GameMode::Survival => "Dots don't come back and cores burn energy, last one left wins.",
+        GameMode::BlackholeRace => "First core to collapse into a blackhole wins.",
+    }
+}
+
+pub fn settings(
+    ctx: &egui::CtxRef,
+    paused: bool,
+    camera: &mut CameraMode,
+    input_map: &InputMap,
+    debug: &mut DebugPanel,
+    config: &mut GameConfig,
+) -> Option<Action> {
+    let back = Action::Show(if paused {
+        Screen::Paused
+    } else {
+        Screen::Title
+    });
+    let mut action = keys(ctx, input_map, back, back);
+    menu(ctx, "settings", |ui| {
+        ui.heading("Settings");
+        ui.add_space(10.0);
+        let key = |control| input_map.binding(control);
+        ui.label("Local players");
+        let text = format!("share one view ({})", key(Control::Camera));
+        ui.radio_value(camera, CameraMode::Shared, text);
+        let text = format!("split the screen ({})", key(Control::Camera));
+        ui.radio_value(camera, CameraMode::Split, text);
+        ui.separator();
+        if button(ui, "Controls") {
+            action = Some(Action::Show(Screen::Controls { paused }));
+        }
+        ui.separator();
+        let mut open = debug.is_open();
+        let text = format!("Debug panel ({})", key(Control::DebugPanel));
+        if ui.checkbox(&mut open, text).changed() {
+            debug.toggle();
+        }
+        if button(ui, "Reset tuning to defaults") {
+            *config = GameConfig::default();
+        }
+        ui.separator();
+        if button(ui, &with_pause(input_map, "Back")) {
+            action = Some(back);
+        }
+    });
+    action
+}
+
+/// Bindings of every control, a clicked one waits for the next key or button in `rebinding`.
+/// `pad_pressed` is a gamepad button pressed this frame.
+pub fn controls(
+    ctx: &egui::CtxRef,
+    paused: bool,
+    input_map: &mut InputMap,
+    rebinding: &mut Option<Control>,
+    pad_pressed: Option<PadButton>,
+) -> Option<Action> {
+    let back = Action::Show(Screen::Settings { paused });
+    let mut action = None;
+    match *rebinding {
+        // Enter and the pause binding are bound like any other key while listening.
+        Some(control) => {
+            if input_map.rebind(ctx.input(), pad_pressed, control) {
+                *rebinding = None;
+            }
+        }
+        None => action = keys(ctx, input_map, back, back),
+    }
+    menu(ctx, "controls", |ui| {
+        ui.heading("Controls");
+        ui.add_space(10.0);
+        ui.label("A single player steers with the keys of players 1 and 2.");
+        ui.label("A key taken by another control swaps with it.");
+        egui::Grid::new("steering").show(ui, |ui| {
+            ui.label("");
+            for dir in Dir::ALL {
+                ui.label(format!("{:?}", dir));
+            }
+            ui.end_row();
+            for set in 0..MAX_PLAYERS {
+                ui.label(format!("Player {}", set + 1));
+                for dir in Dir::ALL {
+                    binding(ui, input_map, rebinding, Control::Steer { set, dir });
+                }
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        egui::Grid::new("controls").show(ui, |ui| {
+            for control in Control::KEYS
+                .iter()
+                .copied()
+                .chain([Control::PadPause, Control::PadAbility])
+            {
+                ui.label(control.name());
+                binding(ui, input_map, rebinding, control);
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        ui.label("Pointer of a single player");
+        ui.radio_value(&mut input_map.pointer, PointerMode::Hold, "hold to steer");
+        ui.radio_value(
+            &mut input_map.pointer,
+            PointerMode::Tap,
+            "tap to move there",
+        );
+        let dead_zone = &mut input_map.pad_dead_zone;
+        ui.add(egui::Slider::new(dead_zone, 0.0..=0.9).text("gamepad dead zone"));
+        ui.separator();
+        if button(ui, "Reset controls to defaults") {
+            *input_map = InputMap::default();
+            *rebinding = None;
+        }
+        if rebinding.is_some() {
+            if button(ui, "Cancel") {
+                *rebinding = None;
+            }
+        } else if button(ui, &with_pause(input_map, "Back")) {
+            action = Some(back);
+        }
+    });
+    action
+}
+
+/// Button showing what `control` is bound to, clicking it listens for a new binding.
+fn binding(
+    ui: &mut egui::Ui,
+    input_map: &InputMap,
+    rebinding: &mut Option<Control>,
+    control: Control,
+) {
+    let text = if *rebinding == Some(control) {
+        "press…".to_owned()
+    } else {
+        input_map.binding(control)
+    };
+    if ui
+        .add_sized([90.0, 20.0], egui::Button::new(text))
+        .clicked()
+    {
+        *rebinding = Some(control);
+    }
+}
+
+/// `online` games keep running, they can only be left.
+pub fn paused(
+    ctx: &egui::CtxRef,
+    input_map: &InputMap,
+    online: bool,
+    can_quit: bool,
+) -> Option<Action> {
+    let resume = Action::Show(Screen::Playing);
+    let mut action = keys(ctx, input_map, resume, resume);
+    menu(ctx, "paused", |ui| {
+        ui.heading("Paused");
+        ui.add_space(10.0);
+        if button(ui, &with_pause(input_map, "Resume")) {
+            action = Some(resume);
+        }
+        if online {
+            if button(ui, "Leave server") {
+                action = Some(Action::Leave);
+            }
+        } else {
+            if button(ui, "Restart") {
+                action = Some(Action::Start);
+            }
+            if button(ui, "Settings") {
+                action = Some(Action::Show(Screen::Settings { paused: true }));
+            }
+            if button(ui, "Main menu") {
+                action = Some(Action::Show(Screen::Title));
+            }
+        }
+        if can_quit && button(ui, "Quit") {
+            action = Some(Action::Quit);
+        }
+    });
+    action
+}
+
+/// Results of the round, or what the world was like before Zen started it over.
+/// `records` are the players who made the high scores, with their place.
+pub fn round_over(
+    ctx: &egui::CtxRef,
+    input_map: &InputMap,
+    game: &Game,
+    records: &[(String, usize)],
+    dt: f64,
+) -> Option<Action> {
+    let state = &game.state;
+    let (results, next) = match (state.results(), state.drained()) {
+        (Some(results), _) => (results, Action::Start),
+        (None, Some(drained)) => (drained, Action::Show(Screen::Playing)),
+        (None, None) => return Some(Action::Show(Screen::Playing)),
+    };
+    let mut action = keys(ctx, input_map, next, Action::Show(Screen::Title));
+    menu(ctx, "round over", |ui| {
+        round::summary(ui, game, results, dt);
+        ui.label(format!("Free dots left: {}", state.free_dots()));
+        for (label, place) in records {
+            let who = if game.players.len() > 1 {
+                format!("{} is", label)
+            } else {
+                "You are".to_owned()
+            };
+            let text = format!("New high score, {} number {}", who, place + 1);
+            ui.label(egui::Label::new(text).strong());
+        }
+        ui.separator();
+        let text = match next {
+            Action::Start => "Play again (Enter)",
+            _ => "Continue (Enter)",
+        };
+        if button(ui, text) {
+            action = Some(next);
+        }
+        if button(ui, "Change mode") {
+            action = Some(Action::Show(Screen::ModeSelect));
+        }
+        if button(ui, &with_pause(input_map, "Main menu")) {
+            action = Some(Action::Show(Screen::Title));
+        }
+    });
+    action
+}
+
+pub fn high_scores(
+    ctx: &egui::CtxRef,
+    input_map: &InputMap,
+    scores: &HighScores,
+    mode: GameMode,
+    dt: f64,
+) -> Option<Action> {
+    let back = Action::Show(Screen::Title);
+    let mut action = keys(ctx, input_map, back, back);
+    menu(ctx, "high scores", |ui| {
+        ui.heading("High scores");
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            for &other in &GameMode::ALL {
+                if ui.selectable_label(other == mode, other.name()).clicked() {
+                    action = Some(Action::Show(Screen::HighScores { mode: other }));
+                }
+            }
+        });
+        ui.separator();
+        let mut empty = true;
+        egui::Grid::new("high scores").show(ui, |ui| {
+            for (place, score) in scores.table(mode).enumerate() {
+                empty = false;
+                ui.label(format!("{}.", place + 1));
+                ui.label(match mode {
+                    GameMode::Zen | GameMode::TimeAttack => format!("energy {:.2}", score.value),
+                    GameMode::Survival => format!("survived {}", clock(score.value as u32, dt)),
+                    GameMode::BlackholeRace => {
+                        format!("blackhole in {}", clock(score.value as u32, dt))
+                    }
+                });
+                ui.label(format!(
+                    "{} dots drained, peak size {:.2}",
+                    score.dots_drained, score.peak_size
+                ));
+                let rivals = match score.rivals {
+                    Some(difficulty) => format!("vs {}", difficulty.name()),
+                    None => "solo".to_owned(),
+                };
+                let players = match score.players {
+                    1 => rivals,
+                    players => format!("{} players, {}", players, rivals),
+                };
+                ui.label(players);
+                ui.end_row();
+            }
+        });
+        if empty {
+            ui.label("No rounds yet");
+        }
+        ui.separator();
+        if button(ui, &with_pause(input_map, "Back")) {
+            action = Some(back);
+        }
+    });
+    action
+}
+
+/// Enter confirms a menu, the pause binding backs out of it.
+fn keys(ctx: &egui::CtxRef, input_map: &InputMap, enter: Action, back: Action) -> Option<Action> {
+    let input = ctx.input();
+    if input.key_pressed(Key::Enter) {
+        Some(enter)
+    } else if input_map.pressed(input, Control::Pause) {
+        Some(back)
+    } else {
+        None
+    }
+}
+
+/// Label of a button which the pause binding presses too.
+fn with_pause(input_map: &InputMap, text: &str) -> String {
+    format!("{} ({})", text, input_map.binding(Control::Pause))
+}
+
+fn menu(ctx: &egui::CtxRef, id: &str, add_contents: impl FnOnce(&mut egui::Ui)) {
+    egui::Window::new(id)
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| ui.vertical_centered(add_contents));
+}
+
+fn button(ui: &mut egui::Ui, text: &str) -> bool {
+    ui.add_sized([200.0, 24.0], egui::Button::new(text))
+        .clicked()
+}
@@ -0,0 +1,55 @@
+/// Accumulates frame time and tells how many fixed simulation steps to run,
+/// so the game speed doesn't depend on the monitor refresh rate.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    ticks_per_second: f64,
+    max_steps: usize,
+    accumulator: f64,
+    last_time: Option<f64>,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(60.0, 5)
+    }
+}
+
+impl FixedTimestep {
+    /// `max_steps` limits catch-up after a stall: the rest of the lag is dropped
+    /// and the simulation slows down instead of freezing the frame.
+    pub fn new(ticks_per_second: f64, max_steps: usize) -> Self {
+        assert!(ticks_per_second > 0.0);
+        Self {
+            ticks_per_second,
+            max_steps: max_steps.max(1),
+            accumulator: 0.0,
+            last_time: None,
+        }
+    }
+    pub fn dt(&self) -> f64 {
+        1.0 / self.ticks_per_second
+    }
+    /// Feeds the current time in seconds, returns number of steps to simulate.
+    pub fn advance(&mut self, time: f64) -> usize {
+        let elapsed = match self.last_time.replace(time) {
+            Some(last) => (time - last).max(0.0),
+            None => 0.0,
+        };
+        self.accumulator += elapsed;
+
+        let dt = self.dt();
+        let mut steps = 0;
+        while self.accumulator >= dt && steps < self.max_steps {
+            self.accumulator -= dt;
+            steps += 1;
+        }
+        if steps == self.max_steps {
+            self.accumulator = self.accumulator.min(dt);
+        }
+        steps
+    }
+    /// How far we are between the last simulated step and the next one, in `0.0..=1.0`.
+    pub fn alpha(&self) -> f64 {
+        (self.accumulator / self.dt()).clamp(0.0, 1.0)
+    }
+}
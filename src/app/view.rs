@@ -0,0 +1,215 @@
+use eframe::egui::{Pos2, Rect};
+use glam::DVec2;
+
+/// Pixels per world unit when following a single small core.
+const SCALE: f64 = 40.0;
+/// World units kept around the outermost cores of a shared view.
+const MARGIN: f64 = 8.0;
+/// Wheel and pinch zoom range, on top of the automatic zoom.
+const ZOOM: (f64, f64) = (0.25, 4.0);
+/// How fast a camera catches up with its shot, per second.
+const FOLLOW: f64 = 6.0;
+/// Shake in pixels when the followed cores lose an orbiting dot, a fraction of it when they gain one.
+const SHAKE: f64 = 12.0;
+/// How fast a shake fades out, per second.
+const SHAKE_FADE: f64 = 5.0;
+
+/// How local players share the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CameraMode {
+    /// One view which frames every core, zooming out as they spread.
+    #[default]
+    Shared,
+    /// Screen is split into a view per player.
+    Split,
+}
+
+impl CameraMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            CameraMode::Shared => CameraMode::Split,
+            CameraMode::Split => CameraMode::Shared,
+        }
+    }
+    /// Part of `rect` and what it should show for every camera.
+    /// `cores` has a focus for every player, `None` for those without a core.
+    pub fn shots(self, rect: Rect, cores: &[Option<Focus>]) -> Vec<(Rect, Shot)> {
+        if self == CameraMode::Shared || cores.len() <= 1 {
+            return vec![(rect, Shot::framing(rect, cores))];
+        }
+        let columns = 2;
+        let rows = cores.len().div_ceil(columns);
+        let size = rect.size() / egui_vec2(columns, rows);
+        cores
+            .iter()
+            .enumerate()
+            .map(|(player, core)| {
+                let min = rect.min + size * egui_vec2(player % columns, player / columns);
+                let rect = Rect::from_min_size(min, size);
+                (rect, Shot::framing(rect, std::slice::from_ref(core)))
+            })
+            .collect()
+    }
+}
+
+fn egui_vec2(x: usize, y: usize) -> eframe::egui::Vec2 {
+    eframe::egui::vec2(x as f32, y as f32)
+}
+
+/// Core a camera keeps in view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Focus {
+    pub pos: DVec2,
+    pub size: f64,
+    pub orbiting_dots: usize,
+}
+
+/// World units around a core which should stay on screen, its spiral grows with `Size`.
+fn reach(size: f64) -> f64 {
+    size.sqrt() + size * 0.5 + 2.0
+}
+
+/// Where a camera is heading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shot {
+    center: DVec2,
+    scale: f64,
+    /// Orbiting dots of the framed cores, the camera shakes when they change.
+    orbiting_dots: usize,
+}
+
+impl Shot {
+    /// Frames every core, zooming out as they spread apart or grow.
+    fn framing(rect: Rect, cores: &[Option<Focus>]) -> Self {
+        let mut cores = cores.iter().flatten();
+        let first = match cores.next() {
+            Some(first) => *first,
+            None => {
+                return Self {
+                    center: DVec2::ZERO,
+                    scale: SCALE,
+                    orbiting_dots: 0,
+                }
+            }
+        };
+        let (min, max, size, orbiting_dots) = cores.fold(
+            (first.pos, first.pos, first.size, first.orbiting_dots),
+            |(min, max, size, dots), core| {
+                (
+                    min.min(core.pos),
+                    max.max(core.pos),
+                    size.max(core.size),
+                    dots + core.orbiting_dots,
+                )
+            },
+        );
+        let margin = if min == max {
+            reach(size)
+        } else {
+            MARGIN.max(reach(size))
+        };
+        let extent = max - min + DVec2::splat(margin * 2.0);
+        let scale = SCALE
+            .min(rect.width() as f64 / extent.x)
+            .min(rect.height() as f64 / extent.y);
+        Self {
+            center: (min + max) * 0.5,
+            scale,
+            orbiting_dots,
+        }
+    }
+}
+
+/// Follows its shot with some lag, can be zoomed and shaken.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    center: DVec2,
+    scale: f64,
+    /// Wheel and pinch zoom, multiplies the scale of the shot.
+    zoom: f64,
+    /// Pixels the view is thrown around by.
+    shake: f64,
+    /// Seconds since the camera was made, drives the shake.
+    time: f64,
+    /// `None` until the first shot, which the camera jumps to.
+    orbiting_dots: Option<usize>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            center: DVec2::ZERO,
+            scale: SCALE,
+            zoom: 1.0,
+            shake: 0.0,
+            time: 0.0,
+            orbiting_dots: None,
+        }
+    }
+}
+
+impl Camera {
+    /// Moves toward `shot` over `dt` seconds.
+    pub fn follow(&mut self, shot: Shot, dt: f64) {
+        let scale = shot.scale * self.zoom;
+        match self.orbiting_dots {
+            None => {
+                self.center = shot.center;
+                self.scale = scale;
+            }
+            Some(dots) => {
+                let t = 1.0 - (-FOLLOW * dt).exp();
+                self.center += (shot.center - self.center) * t;
+                self.scale *= (scale / self.scale).powf(t);
+                if shot.orbiting_dots < dots {
+                    self.shake(SHAKE);
+                } else if shot.orbiting_dots > dots {
+                    self.shake(SHAKE * 0.25);
+                }
+            }
+        }
+        self.orbiting_dots = Some(shot.orbiting_dots);
+        self.shake *= (-SHAKE_FADE * dt).exp();
+        self.time += dt;
+    }
+    /// Multiplies the zoom by `factor`, within limits.
+    pub fn zoom_by(&mut self, factor: f64) {
+        self.zoom = (self.zoom * factor).clamp(ZOOM.0, ZOOM.1);
+    }
+    /// Shakes by `pixels` unless it is already shaking harder.
+    pub fn shake(&mut self, pixels: f64) {
+        self.shake = self.shake.max(pixels);
+    }
+    pub fn view(&self, rect: Rect) -> View {
+        let wobble = DVec2::new((self.time * 53.0).sin(), (self.time * 41.0).cos());
+        View {
+            rect,
+            center: self.center + wobble * self.shake / self.scale,
+            scale: self.scale,
+        }
+    }
+}
+
+/// Part of the screen showing the world around `center`.
+#[derive(Debug, Clone, Copy)]
+pub struct View {
+    pub rect: Rect,
+    center: DVec2,
+    scale: f64,
+}
+
+impl View {
+    /// Pixels per world unit.
+    pub fn scale(self) -> f64 {
+        self.scale
+    }
+    pub fn to_screen(self, pos: DVec2) -> Pos2 {
+        let pos = (pos - self.center) * DVec2::new(self.scale, -self.scale);
+        self.rect.center() + eframe::egui::vec2(pos.x as f32, pos.y as f32)
+    }
+    pub fn to_world(self, pos: Pos2) -> DVec2 {
+        let offset = pos - self.rect.center();
+        self.center
+            + DVec2::new(offset.x as f64, offset.y as f64) / DVec2::new(self.scale, -self.scale)
+    }
+}
@@ -0,0 +1,139 @@
+use super::{controls::PlayerCommand, Game};
+use crate::replay::Replay;
+use eframe::egui::{self, Key};
+
+/// Ticks re-simulated per frame while seeking, keeps the window responsive.
+const SEEK_TICKS_PER_FRAME: usize = 300;
+
+/// Plays a `Replay` back through `State::tick`.
+/// Seeking backwards re-simulates from the start, the world can't be rewound.
+pub struct ReplayViewer {
+    replay: Replay,
+    paused: bool,
+    speed: f64,
+    pending: f64,
+    seek: Option<usize>,
+}
+
+impl ReplayViewer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            paused: false,
+            speed: 1.0,
+            pending: 0.0,
+            seek: None,
+        }
+    }
+    /// Returns `false` when the world stands still and shouldn't be interpolated.
+    pub fn update(&mut self, ctx: &egui::CtxRef, game: &mut Game, steps: usize) -> bool {
+        let mut step_once = false;
+        {
+            let input = ctx.input();
+            if input.key_pressed(Key::Space) {
+                self.paused = !self.paused;
+            }
+            if input.key_pressed(Key::ArrowRight) {
+                self.paused = true;
+                step_once = true;
+            }
+            if input.key_pressed(Key::ArrowUp) {
+                self.speed = (self.speed * 2.0).min(16.0);
+            }
+            if input.key_pressed(Key::ArrowDown) {
+                self.speed = (self.speed * 0.5).max(0.125);
+            }
+        }
+        step_once |= self.ui(ctx, game.ticks);
+
+        if let Some(target) = self.seek {
+            if target < game.ticks {
+                *game = Game::new(
+                    self.replay.seed,
+                    self.replay.config_at(0),
+                    self.replay.players(),
+                    &self.replay.bots,
+                    self.replay.mode,
+                );
+            }
+            let end = target.min(game.ticks + SEEK_TICKS_PER_FRAME);
+            while game.ticks < end && self.step(game) {}
+            if game.ticks >= target {
+                self.seek = None;
+            }
+            false
+        } else if !self.paused {
+            self.pending += steps as f64 * self.speed;
+            while self.pending >= 1.0 {
+                self.pending -= 1.0;
+                if !self.step(game) {
+                    self.paused = true;
+                    self.pending = 0.0;
+                }
+            }
+            !self.paused
+        } else {
+            if step_once {
+                self.step(game);
+            }
+            false
+        }
+    }
+    fn step(&self, game: &mut Game) -> bool {
+        match self.replay.input(game.ticks) {
+            Some(inputs) => {
+                if let Some(config) = self.replay.config_change(game.ticks) {
+                    *game.state.config_mut() = config.clone();
+                }
+                let commands: Vec<PlayerCommand> =
+                    inputs.iter().map(|&input| input.into()).collect();
+                game.step(&commands);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Returns `true` if single step was requested.
+    fn ui(&mut self, ctx: &egui::CtxRef, ticks: usize) -> bool {
+        let mut step_once = false;
+        egui::Window::new("Replay")
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -10.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if self.replay.is_foreign() {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!(
+                            "Recorded with version {}, playback may diverge",
+                            self.replay.version
+                        ),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    let label = if self.paused { "Play" } else { "Pause" };
+                    if ui.button(label).clicked() {
+                        self.paused = !self.paused;
+                    }
+                    if ui.button("Step").clicked() {
+                        self.paused = true;
+                        step_once = true;
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut self.speed, 0.125..=16.0)
+                            .logarithmic(true)
+                            .text("speed"),
+                    );
+                });
+                let mut target = self.seek.unwrap_or(ticks);
+                let slider = egui::Slider::new(&mut target, 0..=self.replay.len()).text("tick");
+                if ui.add(slider).changed() {
+                    self.seek = Some(target);
+                }
+                if self.seek.is_some() {
+                    ui.label("Seeking…");
+                }
+            });
+        step_once
+    }
+}
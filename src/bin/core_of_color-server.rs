@@ -0,0 +1,93 @@
+//! Headless game server, players join with `core_of_color --connect ws://HOST:PORT`
+//! or the web build with `?server=ws://HOST:PORT`.
+//!
+//! `core_of_color-server --addr 0.0.0.0:7878 --seed 42`
+
+use core_of_color::{
+    net::{Server, ServerConfig},
+    state::GameConfig,
+};
+use std::process;
+
+const USAGE: &str = "\
+Usage: core_of_color-server [options]
+  --addr ADDR        address to listen on (default 0.0.0.0:7878)
+  --seed N           world seed (default random)
+  --max-players N    connections beyond this are turned away (default 8)
+  --snapshot-every N broadcast the world every N ticks (default 2)
+  --config FILE      physics configuration in RON";
+
+struct Args {
+    addr: String,
+    config: ServerConfig,
+}
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut addr = "0.0.0.0:7878".to_owned();
+        let mut config = ServerConfig::default();
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{}`", arg))
+            };
+            match arg.as_str() {
+                "--addr" => addr = value()?,
+                "--seed" => config.seed = parse_number(&value()?)?,
+                "--max-players" => {
+                    config.max_players = parse_number(&value()?)?.clamp(1, 255) as usize
+                }
+                "--snapshot-every" => {
+                    config.snapshot_every = parse_number(&value()?)?.clamp(1, 255) as u8
+                }
+                "--config" => config.game = load_config(&value()?)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                other => return Err(format!("unknown argument `{}`", other)),
+            }
+        }
+        Ok(Self { addr, config })
+    }
+}
+
+#[cfg(feature = "persistence")]
+fn load_config(path: &str) -> Result<GameConfig, String> {
+    std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|ron| GameConfig::from_ron(&ron).map_err(|err| err.to_string()))
+        .map_err(|err| format!("{}: {}", path, err))
+}
+#[cfg(not(feature = "persistence"))]
+fn load_config(_path: &str) -> Result<GameConfig, String> {
+    Err("built without `persistence` feature".to_owned())
+}
+
+fn parse_number(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a number, got `{}`", value))
+}
+
+fn main() {
+    let Args { addr, config } = Args::parse().unwrap_or_else(|err| {
+        eprintln!("error: {}\n\n{}", err, USAGE);
+        process::exit(2);
+    });
+    let seed = config.seed;
+    let server = Server::bind(&addr, config).unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", addr, err);
+        process::exit(1);
+    });
+    match server.local_addr() {
+        Ok(addr) => eprintln!("listening on ws://{} with seed {}", addr, seed),
+        Err(err) => eprintln!("listening with seed {}: {}", seed, err),
+    }
+    let result = server.run(|event| eprintln!("{}", event));
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
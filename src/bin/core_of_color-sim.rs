@@ -0,0 +1,336 @@
+//! Headless simulation runner, prints per-tick statistics for balancing.
+//!
+//! `core_of_color-sim --ticks 3600 --seed 42 --policy random --format csv`
+
+use core_of_color::{
+    replay::Replay,
+    state::{Difficulty, GameConfig, GameMode, State},
+};
+use glam::DVec2;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    process,
+};
+
+const USAGE: &str = "\
+Usage: core_of_color-sim [options]
+  --ticks N          number of ticks to simulate (default 3600)
+  --seed N           world seed (default 0)
+  --every N          print statistics every N ticks (default 1)
+  --policy POLICY    idle | random | script:FILE | replay:FILE (default idle)
+  --players N        number of cores, the others stay idle (default 1)
+  --bots N           number of AI rivals (default 0)
+  --difficulty LEVEL easy | normal | hard, of the AI rivals (default normal)
+  --mode MODE        zen | time-attack | survival | blackhole-race (default zen)
+  --format FORMAT    csv | json (default csv)
+  --config FILE      physics configuration in RON
+  --dump-config      print default configuration and exit
+
+Script file: one `TICKS X Y` per line, holds direction (X, Y) for TICKS ticks.
+Replay file: recorded in game with Ctrl+R, its seed, players, rivals, mode and config override the options.
+Statistics are printed for the first player, the run stops early once the round is over.";
+
+enum Policy {
+    Idle,
+    Random { rng: Pcg64Mcg, dir: DVec2 },
+    Script { steps: Vec<(u64, DVec2)> },
+    Replay(Replay),
+}
+impl Policy {
+    fn parse(arg: &str, seed: u64) -> Result<Self, String> {
+        match arg {
+            "idle" => Ok(Policy::Idle),
+            "random" => Ok(Policy::Random {
+                rng: Pcg64Mcg::seed_from_u64(seed.wrapping_add(1)),
+                dir: DVec2::ZERO,
+            }),
+            _ => {
+                if let Some(path) = arg.strip_prefix("replay:") {
+                    let replay = fs::File::open(path)
+                        .and_then(|file| Replay::read_from(io::BufReader::new(file)))
+                        .map_err(|err| format!("{}: {}", path, err))?;
+                    return Ok(Policy::Replay(replay));
+                }
+                let path = arg
+                    .strip_prefix("script:")
+                    .ok_or_else(|| format!("unknown policy `{}`", arg))?;
+                let script =
+                    fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+                Self::parse_script(&script).map(|steps| Policy::Script { steps })
+            }
+        }
+    }
+    fn parse_script(script: &str) -> Result<Vec<(u64, DVec2)>, String> {
+        let mut steps = vec![];
+        let mut until = 0;
+        for (n, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let parse = || -> Option<(u64, DVec2)> {
+                match fields[..] {
+                    [ticks, x, y] => Some((
+                        ticks.parse().ok()?,
+                        DVec2::new(x.parse().ok()?, y.parse().ok()?),
+                    )),
+                    _ => None,
+                }
+            };
+            let (ticks, dir) =
+                parse().ok_or_else(|| format!("script line {}: expected `TICKS X Y`", n + 1))?;
+            until += ticks;
+            steps.push((until, dir));
+        }
+        Ok(steps)
+    }
+    /// Only replays hold the ability button.
+    fn ability(&self, tick: u64, player: usize) -> bool {
+        match self {
+            Policy::Replay(replay) => replay
+                .input(tick as usize)
+                .and_then(|inputs| inputs.get(player))
+                .is_some_and(|input| input.ability),
+            _ => false,
+        }
+    }
+    fn dir(&mut self, tick: u64, player: usize) -> DVec2 {
+        match self {
+            Policy::Replay(replay) => replay
+                .input(tick as usize)
+                .and_then(|inputs| inputs.get(player))
+                .map_or(DVec2::ZERO, |input| input.steer),
+            _ if player > 0 => DVec2::ZERO,
+            Policy::Idle => DVec2::ZERO,
+            Policy::Random { rng, dir } => {
+                if tick.is_multiple_of(60) {
+                    let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                    let strength = rng.gen_range(0.0..=1.0);
+                    *dir = DVec2::new(angle.cos(), angle.sin()) * strength;
+                }
+                *dir
+            }
+            Policy::Script { steps } => steps
+                .iter()
+                .find(|(until, _)| tick < *until)
+                .map_or(DVec2::ZERO, |(_, dir)| *dir),
+        }
+    }
+}
+
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Args {
+    ticks: u64,
+    seed: u64,
+    every: u64,
+    players: usize,
+    bots: Vec<Difficulty>,
+    mode: GameMode,
+    policy: Policy,
+    format: Format,
+    config: GameConfig,
+}
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut ticks = 3600;
+        let mut seed = 0;
+        let mut every = 1;
+        let mut players = 1;
+        let mut bots = 0;
+        let mut difficulty = Difficulty::default();
+        let mut mode = GameMode::default();
+        let mut policy = "idle".to_owned();
+        let mut format = Format::Csv;
+        let mut config = GameConfig::default();
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{}`", arg))
+            };
+            match arg.as_str() {
+                "--ticks" => ticks = parse_number(&value()?)?,
+                "--seed" => seed = parse_number(&value()?)?,
+                "--every" => every = parse_number(&value()?)?.max(1),
+                "--players" => players = parse_number(&value()?)?.max(1) as usize,
+                "--bots" => bots = parse_number(&value()?)? as usize,
+                "--difficulty" => {
+                    let level = value()?;
+                    difficulty = *Difficulty::ALL
+                        .iter()
+                        .find(|difficulty| difficulty.name() == level)
+                        .ok_or_else(|| format!("unknown difficulty `{}`", level))?;
+                }
+                "--mode" => {
+                    let name = value()?;
+                    mode = *GameMode::ALL
+                        .iter()
+                        .find(|mode| mode.name().to_lowercase().replace(' ', "-") == name)
+                        .ok_or_else(|| format!("unknown mode `{}`", name))?;
+                }
+                "--policy" => policy = value()?,
+                "--format" => {
+                    format = match value()?.as_str() {
+                        "csv" => Format::Csv,
+                        "json" => Format::Json,
+                        other => return Err(format!("unknown format `{}`", other)),
+                    }
+                }
+                "--config" => config = load_config(&value()?)?,
+                "--dump-config" => {
+                    println!("{}", dump_config()?);
+                    process::exit(0);
+                }
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                other => return Err(format!("unknown argument `{}`", other)),
+            }
+        }
+        let policy = Policy::parse(&policy, seed)?;
+        let mut bots = vec![difficulty; bots];
+        if let Policy::Replay(replay) = &policy {
+            seed = replay.seed;
+            players = replay.players();
+            bots = replay.bots.clone();
+            mode = replay.mode;
+            config = replay.config_at(0);
+        }
+        Ok(Self {
+            ticks,
+            seed,
+            every,
+            players,
+            bots,
+            mode,
+            policy,
+            format,
+            config,
+        })
+    }
+}
+
+#[cfg(feature = "persistence")]
+fn load_config(path: &str) -> Result<GameConfig, String> {
+    fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|ron| GameConfig::from_ron(&ron).map_err(|err| err.to_string()))
+        .map_err(|err| format!("{}: {}", path, err))
+}
+#[cfg(not(feature = "persistence"))]
+fn load_config(_path: &str) -> Result<GameConfig, String> {
+    Err("built without `persistence` feature".to_owned())
+}
+
+#[cfg(feature = "persistence")]
+fn dump_config() -> Result<String, String> {
+    GameConfig::default()
+        .to_ron()
+        .map_err(|err| err.to_string())
+}
+#[cfg(not(feature = "persistence"))]
+fn dump_config() -> Result<String, String> {
+    Err("built without `persistence` feature".to_owned())
+}
+
+fn parse_number(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a number, got `{}`", value))
+}
+
+fn main() {
+    let mut args = Args::parse().unwrap_or_else(|err| {
+        eprintln!("error: {}\n\n{}", err, USAGE);
+        process::exit(2);
+    });
+    if let Err(err) = run(&mut args) {
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(args: &mut Args) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if let Format::Csv = args.format {
+        writeln!(
+            out,
+            "tick,dots,energy,energy_size,size,orbiting_dots,blackhole"
+        )?;
+    }
+
+    let (mut state, players) = State::new_game_with_bots(args.seed, args.players, &args.bots);
+    *state.config_mut() = args.config.clone();
+    state.set_mode(args.mode);
+    let player = players[0];
+    for tick in 0..args.ticks {
+        if let Some(results) = state.results() {
+            let won = results.winner == Some(player);
+            eprintln!(
+                "{} is over after {} ticks, the player {}",
+                results.mode.name(),
+                results.ticks,
+                if won { "won" } else { "lost" }
+            );
+            break;
+        }
+        for (i, &player) in players.iter().enumerate() {
+            let dir = args.policy.dir(tick, i);
+            state.player_input(player, dir);
+            state.player_ability(player, args.policy.ability(tick, i));
+        }
+        if let Policy::Replay(replay) = &args.policy {
+            if let Some(config) = replay.config_change(tick as usize) {
+                *state.config_mut() = config.clone();
+            }
+        }
+        state.tick();
+
+        if !(tick + 1).is_multiple_of(args.every) {
+            continue;
+        }
+        let dots = state.free_dots();
+        let stats = match state.player_stats(player) {
+            Some(stats) => stats,
+            None => continue,
+        };
+        match args.format {
+            Format::Csv => writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                tick + 1,
+                dots,
+                stats.energy,
+                stats.energy_size,
+                stats.size,
+                stats.orbiting_dots,
+                stats.blackhole,
+            )?,
+            Format::Json => writeln!(
+                out,
+                r#"{{"tick":{},"dots":{},"energy":{},"energy_size":{},"size":{},"orbiting_dots":{},"blackhole":{}}}"#,
+                tick + 1,
+                dots,
+                stats.energy,
+                stats.energy_size,
+                stats.size,
+                stats.orbiting_dots,
+                stats.blackhole,
+            )?,
+        }
+    }
+    out.flush()
+}
@@ -1,5 +1,8 @@
 mod app;
-mod state;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod replay;
+pub mod state;
 pub use app::App;
 
 // ----------------------------------------------------------------------------
@@ -15,6 +18,26 @@ use eframe::wasm_bindgen::{self, prelude::*};
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 pub fn start(canvas_id: &str) -> Result<(), eframe::wasm_bindgen::JsValue> {
+    #[cfg(feature = "net")]
+    let app = match server_param() {
+        Some(url) => App::with_server(url),
+        None => App::new(),
+    };
+    #[cfg(not(feature = "net"))]
     let app = App::new();
     eframe::start_web(canvas_id, Box::new(app))
 }
+
+/// `?server=ws://host:port` in the page URL joins that server.
+#[cfg(all(target_arch = "wasm32", feature = "net"))]
+fn server_param() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let value = search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("server="))?;
+    js_sys::decode_uri_component(value)
+        .ok()?
+        .as_string()
+        .filter(|url| !url.is_empty())
+}
@@ -1,7 +1,45 @@
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
-    let app = core_of_color::App::new();
+    let mut args = std::env::args().skip(1);
+    let app = match (args.next().as_deref(), args.next()) {
+        (Some("--replay"), Some(path)) => {
+            let replay = std::fs::File::open(&path).and_then(|file| {
+                core_of_color::replay::Replay::read_from(std::io::BufReader::new(file))
+            });
+            match replay {
+                Ok(replay) => core_of_color::App::with_replay(replay),
+                Err(err) => {
+                    eprintln!("{}: {}", path, err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        #[cfg(feature = "net")]
+        (Some("--connect"), Some(url)) => core_of_color::App::with_server(url),
+        #[cfg(feature = "net")]
+        (Some("--host"), addr) => host(addr.as_deref().unwrap_or("0.0.0.0:7878")),
+        _ => core_of_color::App::new(),
+    };
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(Box::new(app), native_options);
 }
+
+/// Runs a server on a background thread and joins it, others can join too.
+#[cfg(all(not(target_arch = "wasm32"), feature = "net"))]
+fn host(addr: &str) -> core_of_color::App {
+    use core_of_color::net::{Server, ServerConfig};
+    let server = Server::bind(addr, ServerConfig::default()).and_then(|server| {
+        let port = server.local_addr()?.port();
+        Ok((server, port))
+    });
+    let (server, port) = match server {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("{}: {}", addr, err);
+            std::process::exit(1);
+        }
+    };
+    std::thread::spawn(move || server.run(|event| eprintln!("{}", event)));
+    core_of_color::App::with_server(format!("ws://127.0.0.1:{}", port))
+}
@@ -0,0 +1,116 @@
+use super::protocol::{
+    ClientMessage, CoreSnapshot, DotSnapshot, ServerMessage, Snapshot, PROTOCOL,
+};
+use super::Connection;
+use glam::DVec2;
+use std::collections::HashMap;
+
+/// Connection to a server, draws the world from the two latest snapshots.
+pub struct Client {
+    url: String,
+    connection: Connection,
+    slot: Option<usize>,
+    ticks_per_second: f64,
+    /// Ticks between two snapshots.
+    snapshot_every: f64,
+    /// Positions of the snapshot before `current`, by dot id.
+    previous: HashMap<u64, DVec2>,
+    previous_cores: HashMap<usize, DVec2>,
+    current: Snapshot,
+    /// Time the current snapshot arrived at.
+    received: f64,
+    /// Last input sent to the server, with the ability button.
+    input: Option<(DVec2, bool)>,
+}
+
+impl Client {
+    /// Native clients connect right away, web ones finish connecting in the background.
+    pub fn connect(url: &str) -> Result<Self, String> {
+        Ok(Self {
+            url: url.to_owned(),
+            connection: Connection::open(url)?,
+            slot: None,
+            ticks_per_second: 60.0,
+            snapshot_every: 2.0,
+            previous: HashMap::new(),
+            previous_cores: HashMap::new(),
+            current: Snapshot::default(),
+            received: 0.0,
+            input: None,
+        })
+    }
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+    /// Player slot of our core, `None` until the server welcomes us.
+    pub fn slot(&self) -> Option<usize> {
+        self.slot
+    }
+    /// Sends `steer` and `ability` if they changed and takes in everything the server sent since the last call.
+    /// Fails once the connection is lost.
+    pub fn update(&mut self, time: f64, steer: DVec2, ability: bool) -> Result<(), String> {
+        for message in self.connection.receive()? {
+            match ServerMessage::decode(&message).map_err(|err| err.to_string())? {
+                ServerMessage::Welcome {
+                    protocol,
+                    slot,
+                    ticks_per_second,
+                    snapshot_every,
+                } => {
+                    if protocol != PROTOCOL {
+                        return Err("server runs another version of the game".to_owned());
+                    }
+                    self.slot = Some(slot);
+                    self.ticks_per_second = ticks_per_second as f64;
+                    self.snapshot_every = snapshot_every as f64;
+                }
+                ServerMessage::Full => return Err("server is full".to_owned()),
+                ServerMessage::Snapshot(snapshot) => self.push(time, snapshot),
+            }
+        }
+        // Server says hello once the connection is open, nothing can be sent before that.
+        if self.slot.is_some() && self.input != Some((steer, ability)) {
+            let message = ClientMessage::Input { steer, ability };
+            self.connection.send(message.encode())?;
+            self.input = Some((steer, ability));
+        }
+        Ok(())
+    }
+    fn push(&mut self, time: f64, snapshot: Snapshot) {
+        let current = std::mem::replace(&mut self.current, snapshot);
+        self.previous.clear();
+        self.previous
+            .extend(current.dots.iter().map(|dot| (dot.id, dot.pos)));
+        self.previous_cores.clear();
+        self.previous_cores
+            .extend(current.cores.iter().map(|core| (core.slot, core.pos)));
+        self.received = time;
+    }
+    /// How far we are from the previous snapshot to the current one, in `0.0..=1.0`.
+    /// Snapshots are drawn one interval late, so there is always something to move towards.
+    pub fn alpha(&self, time: f64) -> f64 {
+        let interval = self.snapshot_every / self.ticks_per_second;
+        ((time - self.received) / interval).clamp(0.0, 1.0)
+    }
+    /// Tick of the simulation as drawn with `alpha`.
+    pub fn tick(&self, alpha: f64) -> f64 {
+        self.current.tick as f64 - self.snapshot_every * (1.0 - alpha)
+    }
+    pub fn cores(&self, alpha: f64) -> impl '_ + Iterator<Item = CoreSnapshot> {
+        self.current.cores.iter().map(move |core| CoreSnapshot {
+            pos: lerp(self.previous_cores.get(&core.slot), core.pos, alpha),
+            ..*core
+        })
+    }
+    pub fn dots(&self, alpha: f64) -> impl '_ + Iterator<Item = DotSnapshot> {
+        self.current.dots.iter().map(move |dot| DotSnapshot {
+            pos: lerp(self.previous.get(&dot.id), dot.pos, alpha),
+            ..*dot
+        })
+    }
+}
+
+/// New dots appear where they are.
+fn lerp(previous: Option<&DVec2>, pos: DVec2, alpha: f64) -> DVec2 {
+    previous.map_or(pos, |previous| previous.lerp(pos, alpha))
+}
@@ -0,0 +1,21 @@
+//! Online play: a headless server runs the only simulation and broadcasts snapshots,
+//! clients just send the direction their core is steered in.
+
+mod client;
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+mod protocol;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+pub use client::Client;
+pub use protocol::{ClientMessage, CoreSnapshot, DotSnapshot, ServerMessage, Snapshot, PROTOCOL};
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{Server, ServerConfig};
+
+#[cfg(not(target_arch = "wasm32"))]
+use native::Connection;
+#[cfg(target_arch = "wasm32")]
+use web::Connection;
@@ -0,0 +1,87 @@
+use std::{
+    io::{self, ErrorKind},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+use tungstenite::{client::IntoClientRequest, Message, WebSocket};
+
+/// Joining runs on the UI thread, an unreachable server may only freeze the window this long.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// WebSocket over a plain TCP stream, polled without blocking after the handshake.
+pub struct Connection {
+    socket: WebSocket<TcpStream>,
+}
+
+impl Connection {
+    pub fn open(url: &str) -> Result<Self, String> {
+        let request = url.into_client_request().map_err(|err| err.to_string())?;
+        let uri = request.uri();
+        if uri.scheme_str() != Some("ws") {
+            return Err("only ws:// servers are supported".to_owned());
+        }
+        let host = uri.host().ok_or("server url has no host")?;
+        let port = uri.port_u16().unwrap_or(80);
+        let stream = connect((host, port)).map_err(|err| err.to_string())?;
+        let (socket, _) = tungstenite::client(request, stream).map_err(|err| err.to_string())?;
+        let stream = socket.get_ref();
+        stream
+            .set_read_timeout(None)
+            .and_then(|()| stream.set_write_timeout(None))
+            .and_then(|()| stream.set_nonblocking(true))
+            .map_err(|err| err.to_string())?;
+        let _ = socket.get_ref().set_nodelay(true);
+        Ok(Self { socket })
+    }
+    pub fn send(&mut self, message: Vec<u8>) -> Result<(), String> {
+        match self.socket.send(Message::Binary(message)) {
+            Ok(()) => Ok(()),
+            // Stays buffered, goes out with the next read.
+            Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+    /// Every message which arrived since the last call.
+    pub fn receive(&mut self) -> Result<Vec<Vec<u8>>, String> {
+        let mut messages = vec![];
+        loop {
+            match self.socket.read() {
+                Ok(Message::Binary(message)) => messages.push(message),
+                // Whatever came before the close is still worth showing.
+                Ok(Message::Close(_)) if messages.is_empty() => {
+                    return Err("server closed the connection".to_owned())
+                }
+                Ok(Message::Close(_)) => return Ok(messages),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {
+                    return Ok(messages)
+                }
+                Err(err) => return Err(err.to_string()),
+            }
+        }
+    }
+}
+
+/// First address of `addr` which answers within the timeout, reads and writes time out too
+/// so a server which never finishes the handshake can't hang us.
+fn connect(addr: impl ToSocketAddrs) -> io::Result<TcpStream> {
+    let mut last_err = io::Error::new(ErrorKind::NotFound, "server address not found");
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+                stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+                return Ok(stream);
+            }
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.socket.flush();
+    }
+}
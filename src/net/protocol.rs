@@ -0,0 +1,318 @@
+use glam::DVec2;
+use std::io::{self, Read};
+
+/// Bumped whenever the encoding changes, client and server have to agree on it.
+pub const PROTOCOL: u8 = 3;
+
+/// Every WebSocket message is a single binary frame with one of these.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    /// Direction the core is steered in and whether the ability is held, until the next input.
+    Input { steer: DVec2, ability: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    /// First message on a new connection.
+    Welcome {
+        protocol: u8,
+        /// Player slot of the client's core.
+        slot: usize,
+        ticks_per_second: u8,
+        /// Snapshot is sent every that many ticks.
+        snapshot_every: u8,
+    },
+    /// Every player slot is taken, the connection is closed after this.
+    Full,
+    Snapshot(Snapshot),
+}
+
+/// State of the world after a tick, only what clients need to draw it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub tick: u32,
+    pub cores: Vec<CoreSnapshot>,
+    pub dots: Vec<DotSnapshot>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoreSnapshot {
+    pub slot: usize,
+    pub pos: DVec2,
+    pub size: f64,
+    pub orbiting_dots: usize,
+    pub blackhole: bool,
+}
+
+/// Cores and orbiting dots are dots too, hollow ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DotSnapshot {
+    /// Same dot keeps its id between snapshots, so clients can interpolate it.
+    /// Bits of the entity with its generation, ids of despawned dots are reused.
+    pub id: u64,
+    pub pos: DVec2,
+    /// Degrees.
+    pub hue: f64,
+    /// Percent.
+    pub saturation: f64,
+    /// Percent.
+    pub lightness: f64,
+    pub size: f32,
+    pub hollow: bool,
+}
+
+impl ClientMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![];
+        match self {
+            ClientMessage::Input { steer, ability } => {
+                out.push(0);
+                write_vec2(&mut out, *steer);
+                out.push(*ability as u8);
+            }
+        }
+        out
+    }
+    pub fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        let r = &mut bytes;
+        let [tag] = read_array(r)?;
+        match tag {
+            0 => {
+                let steer = read_vec2(r)?;
+                let [ability] = read_array(r)?;
+                Ok(ClientMessage::Input {
+                    steer,
+                    ability: ability != 0,
+                })
+            }
+            _ => Err(invalid_data("unknown client message")),
+        }
+    }
+}
+
+impl ServerMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![];
+        match self {
+            ServerMessage::Welcome {
+                protocol,
+                slot,
+                ticks_per_second,
+                snapshot_every,
+            } => {
+                out.extend_from_slice(&[
+                    0,
+                    *protocol,
+                    *slot as u8,
+                    *ticks_per_second,
+                    *snapshot_every,
+                ]);
+            }
+            ServerMessage::Full => out.push(1),
+            ServerMessage::Snapshot(snapshot) => {
+                out.push(2);
+                snapshot.write(&mut out);
+            }
+        }
+        out
+    }
+    pub fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        let r = &mut bytes;
+        let [tag] = read_array(r)?;
+        match tag {
+            0 => {
+                let [protocol, slot, ticks_per_second, snapshot_every] = read_array(r)?;
+                if ticks_per_second == 0 || snapshot_every == 0 {
+                    return Err(invalid_data("bad snapshot rate"));
+                }
+                Ok(ServerMessage::Welcome {
+                    protocol,
+                    slot: slot as usize,
+                    ticks_per_second,
+                    snapshot_every,
+                })
+            }
+            1 => Ok(ServerMessage::Full),
+            2 => Snapshot::read(r).map(ServerMessage::Snapshot),
+            _ => Err(invalid_data("unknown server message")),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Positions go as `f32`, colors and sizes as a few bytes, 23 bytes per dot.
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.tick.to_le_bytes());
+        out.push(self.cores.len().min(255) as u8);
+        for core in self.cores.iter().take(255) {
+            out.push(core.slot as u8);
+            out.push(core.blackhole as u8);
+            write_vec2(out, core.pos);
+            out.extend_from_slice(&(core.size as f32).to_le_bytes());
+            out.extend_from_slice(
+                &(core.orbiting_dots.min(u16::MAX as usize) as u16).to_le_bytes(),
+            );
+        }
+        out.extend_from_slice(&(self.dots.len() as u32).to_le_bytes());
+        for dot in &self.dots {
+            out.extend_from_slice(&dot.id.to_le_bytes());
+            write_vec2(out, dot.pos);
+            let hue = (dot.hue.rem_euclid(360.0) / 360.0 * u16::MAX as f64).round() as u16;
+            out.extend_from_slice(&hue.to_le_bytes());
+            out.push(percent_to_byte(dot.saturation));
+            out.push(percent_to_byte(dot.lightness));
+            let size = (dot.size * 256.0).round().clamp(0.0, u16::MAX as f32) as u16;
+            out.extend_from_slice(&size.to_le_bytes());
+            out.push(dot.hollow as u8);
+        }
+    }
+    fn read(r: &mut impl Read) -> io::Result<Self> {
+        let tick = u32::from_le_bytes(read_array(r)?);
+        let [cores] = read_array(r)?;
+        let cores = (0..cores)
+            .map(|_| {
+                let [slot, blackhole] = read_array(r)?;
+                Ok(CoreSnapshot {
+                    slot: slot as usize,
+                    blackhole: blackhole != 0,
+                    pos: read_vec2(r)?,
+                    size: f32::from_le_bytes(read_array(r)?) as f64,
+                    orbiting_dots: u16::from_le_bytes(read_array(r)?) as usize,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        let dots = u32::from_le_bytes(read_array(r)?);
+        // Length comes from the wire, grow as dots are actually read.
+        let mut snapshot_dots = Vec::with_capacity(dots.min(1 << 16) as usize);
+        for _ in 0..dots {
+            let id = u64::from_le_bytes(read_array(r)?);
+            let pos = read_vec2(r)?;
+            let hue = u16::from_le_bytes(read_array(r)?) as f64 / u16::MAX as f64 * 360.0;
+            let [saturation, lightness] = read_array(r)?;
+            let size = u16::from_le_bytes(read_array(r)?) as f32 / 256.0;
+            let [hollow] = read_array(r)?;
+            snapshot_dots.push(DotSnapshot {
+                id,
+                pos,
+                hue,
+                saturation: byte_to_percent(saturation),
+                lightness: byte_to_percent(lightness),
+                size,
+                hollow: hollow != 0,
+            });
+        }
+        Ok(Self {
+            tick,
+            cores,
+            dots: snapshot_dots,
+        })
+    }
+}
+
+fn percent_to_byte(percent: f64) -> u8 {
+    (percent.clamp(0.0, 100.0) * 2.55).round() as u8
+}
+
+fn byte_to_percent(byte: u8) -> f64 {
+    byte as f64 / 2.55
+}
+
+fn write_vec2(out: &mut Vec<u8>, vec: DVec2) {
+    out.extend_from_slice(&(vec.x as f32).to_le_bytes());
+    out.extend_from_slice(&(vec.y as f32).to_le_bytes());
+}
+
+fn read_vec2(r: &mut impl Read) -> io::Result<DVec2> {
+    let x = f32::from_le_bytes(read_array(r)?);
+    let y = f32::from_le_bytes(read_array(r)?);
+    Ok(DVec2::new(x as f64, y as f64))
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dot(id: u64, hollow: bool) -> DotSnapshot {
+        DotSnapshot {
+            id,
+            pos: DVec2::new(-12.5, 3.25),
+            hue: 200.0,
+            saturation: 80.0,
+            lightness: 50.0,
+            size: 1.5,
+            hollow,
+        }
+    }
+
+    #[test]
+    fn client_messages_survive_a_round_trip() {
+        for ability in [false, true] {
+            let message = ClientMessage::Input {
+                steer: DVec2::new(0.5, -0.25),
+                ability,
+            };
+            assert_eq!(ClientMessage::decode(&message.encode()).unwrap(), message);
+        }
+        assert!(ClientMessage::decode(&[9]).is_err());
+        assert!(ClientMessage::decode(&[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn server_messages_survive_a_round_trip() {
+        let welcome = ServerMessage::Welcome {
+            protocol: PROTOCOL,
+            slot: 3,
+            ticks_per_second: 60,
+            snapshot_every: 2,
+        };
+        assert_eq!(ServerMessage::decode(&welcome.encode()).unwrap(), welcome);
+        let full = ServerMessage::Full;
+        assert_eq!(ServerMessage::decode(&full.encode()).unwrap(), full);
+    }
+
+    #[test]
+    fn snapshot_survives_a_round_trip_within_its_precision() {
+        let snapshot = Snapshot {
+            tick: 1234,
+            cores: vec![CoreSnapshot {
+                slot: 1,
+                pos: DVec2::new(4.0, -8.5),
+                size: 2.25,
+                orbiting_dots: 7,
+                blackhole: true,
+            }],
+            // Ids keep the generation in the high bits.
+            dots: vec![dot(1 << 40 | 17, false), dot(u64::MAX, true)],
+        };
+        let bytes = ServerMessage::Snapshot(snapshot.clone()).encode();
+        // Tag, tick, core count, one core, dot count, then the dots.
+        assert_eq!(bytes.len(), 1 + 4 + 1 + 16 + 4 + 23 * 2);
+        let read = match ServerMessage::decode(&bytes).unwrap() {
+            ServerMessage::Snapshot(read) => read,
+            other => panic!("decoded {:?}", other),
+        };
+        assert_eq!(read.tick, snapshot.tick);
+        assert_eq!(read.cores, snapshot.cores);
+        assert_eq!(read.dots.len(), snapshot.dots.len());
+        for (read, dot) in read.dots.iter().zip(&snapshot.dots) {
+            assert_eq!(
+                (read.id, read.pos, read.size, read.hollow),
+                (dot.id, dot.pos, dot.size, dot.hollow)
+            );
+            assert!((read.hue - dot.hue).abs() < 0.01);
+            assert!((read.saturation - dot.saturation).abs() < 0.2);
+            assert!((read.lightness - dot.lightness).abs() < 0.2);
+        }
+        assert!(ServerMessage::decode(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
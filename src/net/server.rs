@@ -0,0 +1,357 @@
+use super::protocol::{
+    ClientMessage, CoreSnapshot, DotSnapshot, ServerMessage, Snapshot, PROTOCOL,
+};
+use crate::state::{spawn_point, GameConfig, State};
+use glam::DVec2;
+use hecs::Entity;
+use std::{
+    fmt,
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+use tungstenite::{
+    handshake::{
+        server::{NoCallback, ServerHandshake},
+        HandshakeError, MidHandshake,
+    },
+    protocol::WebSocketConfig,
+    Message, WebSocket,
+};
+
+pub const TICKS_PER_SECOND: u8 = 60;
+/// A client which can't keep up with snapshots is dropped once this much is waiting for it.
+const MAX_WRITE_BUFFER: usize = 8 << 20;
+/// Time a new connection gets to finish the WebSocket handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Connections still shaking hands beyond this are turned away.
+const MAX_PENDING: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub seed: u64,
+    pub game: GameConfig,
+    /// Connections beyond this are turned away.
+    pub max_players: usize,
+    /// Snapshot is broadcast every that many ticks.
+    pub snapshot_every: u8,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            seed: rand::random(),
+            game: GameConfig::default(),
+            max_players: 8,
+            snapshot_every: 2,
+        }
+    }
+}
+
+/// Something a host may want to log.
+#[derive(Debug)]
+pub enum Event {
+    Joined { slot: usize, addr: SocketAddr },
+    Left { slot: usize, reason: String },
+    Refused { addr: SocketAddr, reason: String },
+}
+
+/// One line for the log of a host.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Joined { slot, addr } => write!(f, "P{} joined from {}", slot + 1, addr),
+            Event::Left { slot, reason } => write!(f, "P{} left: {}", slot + 1, reason),
+            Event::Refused { addr, reason } => write!(f, "refused {}: {}", addr, reason),
+        }
+    }
+}
+
+struct Client {
+    socket: WebSocket<TcpStream>,
+    slot: usize,
+    player: Entity,
+    input: DVec2,
+    ability: bool,
+}
+
+type Handshake = ServerHandshake<TcpStream, NoCallback>;
+
+/// Connection still upgrading to a WebSocket, the handshake goes on a bit every tick.
+struct Pending {
+    handshake: MidHandshake<Handshake>,
+    addr: SocketAddr,
+    deadline: Instant,
+}
+
+/// Runs the only copy of the simulation, clients send inputs and get snapshots back.
+/// Everything happens on one thread, sockets are polled once a tick.
+pub struct Server {
+    listener: TcpListener,
+    config: ServerConfig,
+    state: State,
+    clients: Vec<Client>,
+    pending: Vec<Pending>,
+    ticks: u32,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs, config: ServerConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let mut state = State::with_seed(config.seed);
+        *state.config_mut() = config.game.clone();
+        state.batch_spawn_dots(1000);
+        Ok(Self {
+            listener,
+            config,
+            state,
+            clients: vec![],
+            pending: vec![],
+            ticks: 0,
+        })
+    }
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+    /// Ticks at a fixed rate forever, unless the listener fails.
+    pub fn run(mut self, mut on_event: impl FnMut(Event)) -> io::Result<()> {
+        let dt = Duration::from_secs_f64(1.0 / TICKS_PER_SECOND as f64);
+        let mut next = Instant::now();
+        loop {
+            for event in self.step()? {
+                on_event(event);
+            }
+            next += dt;
+            let now = Instant::now();
+            if next > now {
+                std::thread::sleep(next - now);
+            } else if now - next > dt * 5 {
+                // Too far behind, slow down instead of catching up.
+                next = now;
+            }
+        }
+    }
+    /// Accepts new players, applies their inputs, simulates a tick and broadcasts it.
+    pub fn step(&mut self) -> io::Result<Vec<Event>> {
+        let mut events = vec![];
+        self.accept(&mut events)?;
+        self.receive(&mut events);
+        for client in &self.clients {
+            self.state.player_input(client.player, client.input);
+            self.state.player_ability(client.player, client.ability);
+        }
+        self.state.tick();
+        self.ticks = self.ticks.wrapping_add(1);
+        if self
+            .ticks
+            .is_multiple_of(self.config.snapshot_every.max(1) as u32)
+        {
+            self.broadcast(&mut events);
+        }
+        Ok(events)
+    }
+
+    /// Takes new connections and goes on with the handshakes of earlier ones.
+    /// Sockets never block, a slow or silent client can't hold up the tick.
+    fn accept(&mut self, events: &mut Vec<Event>) -> io::Result<()> {
+        let now = Instant::now();
+        loop {
+            let (stream, addr) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if self.pending.len() >= MAX_PENDING {
+                let reason = "too many connections shaking hands".to_owned();
+                events.push(Event::Refused { addr, reason });
+                continue;
+            }
+            if let Err(err) = stream.set_nonblocking(true) {
+                let reason = err.to_string();
+                events.push(Event::Refused { addr, reason });
+                continue;
+            }
+            let _ = stream.set_nodelay(true);
+            let config = WebSocketConfig {
+                max_write_buffer_size: MAX_WRITE_BUFFER,
+                max_message_size: Some(1 << 10),
+                ..Default::default()
+            };
+            let result = tungstenite::accept_with_config(stream, Some(config));
+            self.handshaken(result, addr, now + HANDSHAKE_TIMEOUT, events);
+        }
+        for pending in std::mem::take(&mut self.pending) {
+            if now > pending.deadline {
+                let reason = "handshake timed out".to_owned();
+                events.push(Event::Refused {
+                    addr: pending.addr,
+                    reason,
+                });
+                continue;
+            }
+            let result = pending.handshake.handshake();
+            self.handshaken(result, pending.addr, pending.deadline, events);
+        }
+        Ok(())
+    }
+    /// Lets the client in once its handshake is done, keeps it pending until `deadline` otherwise.
+    fn handshaken(
+        &mut self,
+        result: Result<WebSocket<TcpStream>, HandshakeError<Handshake>>,
+        addr: SocketAddr,
+        deadline: Instant,
+        events: &mut Vec<Event>,
+    ) {
+        match result {
+            Ok(socket) => self.join(socket, addr, events),
+            Err(HandshakeError::Interrupted(handshake)) => self.pending.push(Pending {
+                handshake,
+                addr,
+                deadline,
+            }),
+            Err(HandshakeError::Failure(err)) => events.push(Event::Refused {
+                addr,
+                reason: err.to_string(),
+            }),
+        }
+    }
+    /// Greets the client with its slot, or with `Full` before closing the connection.
+    fn join(
+        &mut self,
+        mut socket: WebSocket<TcpStream>,
+        addr: SocketAddr,
+        events: &mut Vec<Event>,
+    ) {
+        let slot = (0..self.config.max_players)
+            .find(|&slot| self.clients.iter().all(|client| client.slot != slot));
+        let greeting = match slot {
+            Some(slot) => ServerMessage::Welcome {
+                protocol: PROTOCOL,
+                slot,
+                ticks_per_second: TICKS_PER_SECOND,
+                snapshot_every: self.config.snapshot_every.max(1),
+            },
+            None => ServerMessage::Full,
+        };
+        // Whatever doesn't fit into the socket yet is buffered and flushed with the snapshots.
+        match socket.send(Message::Binary(greeting.encode())) {
+            Ok(()) => {}
+            Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {}
+            Err(err) => {
+                let reason = err.to_string();
+                events.push(Event::Refused { addr, reason });
+                return;
+            }
+        }
+        let slot = match slot {
+            Some(slot) => slot,
+            None => {
+                let _ = socket.close(None);
+                let _ = socket.flush();
+                let reason = "server is full".to_owned();
+                events.push(Event::Refused { addr, reason });
+                return;
+            }
+        };
+        let pos = spawn_point(slot, self.config.max_players);
+        let player = self.state.spawn_player(pos);
+        self.clients.push(Client {
+            socket,
+            slot,
+            player,
+            input: DVec2::ZERO,
+            ability: false,
+        });
+        events.push(Event::Joined { slot, addr });
+    }
+    fn receive(&mut self, events: &mut Vec<Event>) {
+        let mut left = vec![];
+        for (i, client) in self.clients.iter_mut().enumerate() {
+            if let Err(reason) = Self::receive_from(client) {
+                left.push((i, reason));
+            }
+        }
+        self.drop_clients(left, events);
+    }
+    fn receive_from(client: &mut Client) -> Result<(), String> {
+        loop {
+            match client.socket.read() {
+                Ok(Message::Binary(bytes)) => match ClientMessage::decode(&bytes) {
+                    // Clients steer like local players do, nothing faster.
+                    Ok(ClientMessage::Input { steer, ability }) if steer.is_finite() => {
+                        client.input = steer.clamp_length_max(1.0);
+                        client.ability = ability;
+                    }
+                    Ok(_) => return Err("bad input".to_owned()),
+                    Err(err) => return Err(err.to_string()),
+                },
+                Ok(Message::Close(_)) => return Err("disconnected".to_owned()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {
+                    return Ok(())
+                }
+                Err(err) => return Err(err.to_string()),
+            }
+        }
+    }
+    fn broadcast(&mut self, events: &mut Vec<Event>) {
+        let message = ServerMessage::Snapshot(self.snapshot()).encode();
+        let mut left = vec![];
+        for (i, client) in self.clients.iter_mut().enumerate() {
+            match client.socket.send(Message::Binary(message.clone())) {
+                Ok(()) => {}
+                Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {}
+                Err(err) => left.push((i, err.to_string())),
+            }
+        }
+        self.drop_clients(left, events);
+    }
+    /// `left` is in the order of `clients`.
+    fn drop_clients(&mut self, left: Vec<(usize, String)>, events: &mut Vec<Event>) {
+        for (i, reason) in left.into_iter().rev() {
+            let client = self.clients.remove(i);
+            self.state.remove_player(client.player);
+            events.push(Event::Left {
+                slot: client.slot,
+                reason,
+            });
+        }
+    }
+    pub fn snapshot(&self) -> Snapshot {
+        let cores = self
+            .clients
+            .iter()
+            .filter_map(|client| {
+                let stats = self.state.player_stats(client.player)?;
+                Some(CoreSnapshot {
+                    slot: client.slot,
+                    pos: self.state.position(client.player)?.vec,
+                    size: stats.size,
+                    orbiting_dots: stats.orbiting_dots,
+                    blackhole: stats.blackhole,
+                })
+            })
+            .collect();
+        let dots = self
+            .state
+            .dots(1.0)
+            .iter()
+            .map(|dot| DotSnapshot {
+                id: dot.entity.to_bits(),
+                pos: dot.pos.vec,
+                hue: dot.color.hue(),
+                saturation: dot.color.saturation(),
+                lightness: dot.color.lightness(),
+                size: dot.size,
+                hollow: dot.hollow,
+            })
+            .collect();
+        Snapshot {
+            tick: self.ticks,
+            cores,
+            dots,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use eframe::wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use std::{cell::RefCell, rc::Rc};
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+/// Messages arrive in callbacks of the browser, they wait here for the next frame.
+#[derive(Default)]
+struct Inbox {
+    messages: Vec<Vec<u8>>,
+    closed: Option<String>,
+}
+
+/// Browser WebSocket.
+pub struct Connection {
+    socket: WebSocket,
+    inbox: Rc<RefCell<Inbox>>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut(JsValue)>,
+    _on_error: Closure<dyn FnMut(JsValue)>,
+}
+
+impl Connection {
+    pub fn open(url: &str) -> Result<Self, String> {
+        let socket = WebSocket::new(url).map_err(js_error)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+        let inbox = Rc::new(RefCell::new(Inbox::default()));
+
+        let on_message = {
+            let inbox = inbox.clone();
+            Closure::wrap(Box::new(move |event: MessageEvent| {
+                if let Ok(buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+                    let message = js_sys::Uint8Array::new(&buffer).to_vec();
+                    inbox.borrow_mut().messages.push(message);
+                }
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+        let on_close = closed_callback(&inbox, "server closed the connection");
+        let on_error = closed_callback(&inbox, "can't reach the server");
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        Ok(Self {
+            socket,
+            inbox,
+            _on_message: on_message,
+            _on_close: on_close,
+            _on_error: on_error,
+        })
+    }
+    pub fn send(&mut self, message: Vec<u8>) -> Result<(), String> {
+        self.socket.send_with_u8_array(&message).map_err(js_error)
+    }
+    /// Every message which arrived since the last call.
+    pub fn receive(&mut self) -> Result<Vec<Vec<u8>>, String> {
+        let mut inbox = self.inbox.borrow_mut();
+        let messages = std::mem::take(&mut inbox.messages);
+        match &inbox.closed {
+            // Whatever came before the close is still worth showing.
+            Some(reason) if messages.is_empty() => Err(reason.clone()),
+            _ => Ok(messages),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        self.socket.set_onerror(None);
+        let _ = self.socket.close();
+    }
+}
+
+/// The first reason wins, an error is followed by a close.
+fn closed_callback(
+    inbox: &Rc<RefCell<Inbox>>,
+    reason: &'static str,
+) -> Closure<dyn FnMut(JsValue)> {
+    let inbox = inbox.clone();
+    Closure::wrap(Box::new(move |_: JsValue| {
+        inbox
+            .borrow_mut()
+            .closed
+            .get_or_insert_with(|| reason.to_owned());
+    }) as Box<dyn FnMut(JsValue)>)
+}
+
+fn js_error(err: JsValue) -> String {
+    err.as_string().unwrap_or_else(|| format!("{:?}", err))
+}
@@ -0,0 +1,359 @@
+use crate::state::{Difficulty, GameConfig, GameMode};
+use glam::DVec2;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 8] = b"COCREPLY";
+/// Bumped whenever the layout changes, older files are refused.
+const FORMAT: u8 = 1;
+
+/// What a player did on a tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Input {
+    /// Direction of the push, up to unit length.
+    pub steer: DVec2,
+    pub ability: bool,
+}
+
+/// Seed and per-tick input of every player in a session, enough to reproduce it with `State::tick`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub version: String,
+    pub seed: u64,
+    players: usize,
+    /// AI rivals spawned after the players, see `State::new_game_with_bots`.
+    pub bots: Vec<Difficulty>,
+    pub mode: GameMode,
+    /// Tuning from the tick it took effect on, the default until the first change.
+    configs: Vec<(u64, GameConfig)>,
+    /// Ticks up to which every run lasts, a held key is a single run.
+    /// Runs stay encoded, a file could claim more ticks than fit in memory.
+    ends: Vec<u64>,
+    /// Input of every player for a run, then the next run.
+    inputs: Vec<Input>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, players: usize) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            seed,
+            players: players.max(1),
+            bots: vec![],
+            mode: GameMode::default(),
+            configs: vec![],
+            ends: vec![],
+            inputs: vec![],
+        }
+    }
+    pub fn players(&self) -> usize {
+        self.players
+    }
+    /// Tuning and input of every player for the next tick.
+    pub fn push(&mut self, config: &GameConfig, inputs: &[Input]) {
+        debug_assert_eq!(inputs.len(), self.players);
+        let tick = self.ends.last().copied().unwrap_or(0);
+        if *config != self.config_at(tick as usize) {
+            self.configs.push((tick, config.clone()));
+        }
+        let frame = (0..self.players).map(|player| inputs.get(player).copied().unwrap_or_default());
+        let start = self.inputs.len().saturating_sub(self.players);
+        match self.ends.last_mut() {
+            Some(end) if self.inputs[start..].iter().copied().eq(frame.clone()) => *end += 1,
+            _ => {
+                let end = self.ends.last().copied().unwrap_or(0) + 1;
+                self.ends.push(end);
+                self.inputs.extend(frame);
+            }
+        }
+    }
+    pub fn input(&self, tick: usize) -> Option<&[Input]> {
+        let run = self.ends.partition_point(|&end| end <= tick as u64);
+        self.inputs
+            .get(run * self.players..(run + 1) * self.players)
+    }
+    /// Tuning in effect on `tick`.
+    pub fn config_at(&self, tick: usize) -> GameConfig {
+        let changes = self
+            .configs
+            .partition_point(|&(start, _)| start <= tick as u64);
+        match changes.checked_sub(1) {
+            Some(last) => self.configs[last].1.clone(),
+            None => GameConfig::default(),
+        }
+    }
+    /// Tuning that changed on `tick`.
+    pub fn config_change(&self, tick: usize) -> Option<&GameConfig> {
+        self.configs
+            .iter()
+            .find(|&&(start, _)| start == tick as u64)
+            .map(|(_, config)| config)
+    }
+    /// Number of recorded ticks.
+    pub fn len(&self) -> usize {
+        let ticks = self.ends.last().copied().unwrap_or(0);
+        ticks.min(usize::MAX as u64) as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+    /// Recorded with another version of the game, simulation may diverge.
+    pub fn is_foreign(&self) -> bool {
+        self.version != env!("CARGO_PKG_VERSION")
+    }
+
+    /// Inputs are run-length encoded, held keys take a few bytes per press.
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[FORMAT])?;
+        let version = self.version.as_bytes();
+        w.write_all(&[version.len().min(255) as u8])?;
+        w.write_all(&version[..version.len().min(255)])?;
+        w.write_all(&self.seed.to_le_bytes())?;
+        w.write_all(&[self.players.min(255) as u8])?;
+        let bots = &self.bots[..self.bots.len().min(255)];
+        w.write_all(&[bots.len() as u8])?;
+        for &bot in bots {
+            let level = Difficulty::ALL.iter().position(|&level| level == bot);
+            w.write_all(&[level.unwrap_or_default() as u8])?;
+        }
+        let mode = GameMode::ALL.iter().position(|&mode| mode == self.mode);
+        w.write_all(&[mode.unwrap_or_default() as u8])?;
+        w.write_all(&(self.configs.len() as u32).to_le_bytes())?;
+        for (tick, config) in &self.configs {
+            let config = encode_config(config)?;
+            w.write_all(&tick.to_le_bytes())?;
+            w.write_all(&(config.len() as u32).to_le_bytes())?;
+            w.write_all(config.as_bytes())?;
+        }
+
+        // Runs longer than a count can hold are split.
+        let mut runs = vec![];
+        let mut start = 0;
+        for (&end, inputs) in self.ends.iter().zip(self.inputs.chunks(self.players)) {
+            let mut ticks = end - start;
+            while ticks > 0 {
+                let count = ticks.min(u32::MAX as u64);
+                runs.push((count as u32, inputs));
+                ticks -= count;
+            }
+            start = end;
+        }
+        w.write_all(&(runs.len() as u32).to_le_bytes())?;
+        for (count, inputs) in runs {
+            w.write_all(&count.to_le_bytes())?;
+            for input in inputs {
+                w.write_all(&input.steer.x.to_le_bytes())?;
+                w.write_all(&input.steer.y.to_le_bytes())?;
+                w.write_all(&[input.ability as u8])?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from(mut r: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a replay file"));
+        }
+        let [format] = read_array(&mut r)?;
+        if format != FORMAT {
+            return Err(invalid_data("unsupported replay format"));
+        }
+        let [len] = read_array(&mut r)?;
+        let mut version = vec![0; len as usize];
+        r.read_exact(&mut version)?;
+        let version = String::from_utf8(version).map_err(|_| invalid_data("bad version"))?;
+        let seed = u64::from_le_bytes(read_array(&mut r)?);
+        let [players] = read_array(&mut r)?;
+        let players = players as usize;
+        if players == 0 {
+            return Err(invalid_data("replay without players"));
+        }
+        let [count] = read_array(&mut r)?;
+        let mut bots = vec![];
+        for _ in 0..count {
+            let [level] = read_array(&mut r)?;
+            let bot = Difficulty::ALL.get(level as usize);
+            bots.push(*bot.ok_or_else(|| invalid_data("unknown AI difficulty"))?);
+        }
+        let [index] = read_array(&mut r)?;
+        let mode = GameMode::ALL.get(index as usize);
+        let mode = *mode.ok_or_else(|| invalid_data("unknown game mode"))?;
+        let changes = u32::from_le_bytes(read_array(&mut r)?);
+        let mut configs = vec![];
+        for _ in 0..changes {
+            let tick = u64::from_le_bytes(read_array(&mut r)?);
+            let len = u32::from_le_bytes(read_array(&mut r)?);
+            let mut config = vec![];
+            r.by_ref().take(len as u64).read_to_end(&mut config)?;
+            if config.len() != len as usize {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let config = String::from_utf8(config).map_err(|_| invalid_data("bad config"))?;
+            configs.push((tick, decode_config(&config)?));
+        }
+
+        // Counts come from the file, runs grow as they are actually read.
+        let runs = u32::from_le_bytes(read_array(&mut r)?);
+        let mut ends = vec![];
+        let mut inputs = vec![];
+        let mut end = 0;
+        for _ in 0..runs {
+            let count = u32::from_le_bytes(read_array(&mut r)?);
+            for _ in 0..players {
+                let x = f64::from_le_bytes(read_array(&mut r)?);
+                let y = f64::from_le_bytes(read_array(&mut r)?);
+                let [ability] = read_array(&mut r)?;
+                inputs.push(Input {
+                    steer: DVec2::new(x, y),
+                    ability: ability != 0,
+                });
+            }
+            if count == 0 {
+                inputs.truncate(inputs.len() - players);
+                continue;
+            }
+            end += count as u64;
+            ends.push(end);
+        }
+        Ok(Self {
+            version,
+            seed,
+            players,
+            bots,
+            mode,
+            configs,
+            ends,
+            inputs,
+        })
+    }
+}
+
+#[cfg(feature = "persistence")]
+fn encode_config(config: &GameConfig) -> io::Result<String> {
+    ron::ser::to_string(config).map_err(io::Error::other)
+}
+#[cfg(not(feature = "persistence"))]
+fn encode_config(_config: &GameConfig) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "tuned config needs the `persistence` feature",
+    ))
+}
+
+#[cfg(feature = "persistence")]
+fn decode_config(config: &str) -> io::Result<GameConfig> {
+    GameConfig::from_ron(config).map_err(|err| invalid_data(&err.to_string()))
+}
+#[cfg(not(feature = "persistence"))]
+fn decode_config(_config: &str) -> io::Result<GameConfig> {
+    Err(invalid_data("tuned config needs the `persistence` feature"))
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(x: f64, ability: bool) -> Input {
+        Input {
+            steer: DVec2::new(x, -x),
+            ability,
+        }
+    }
+
+    fn round_trip(replay: &Replay) -> Replay {
+        let mut file = vec![];
+        replay.write_to(&mut file).unwrap();
+        Replay::read_from(&file[..]).unwrap()
+    }
+
+    #[test]
+    fn held_inputs_are_single_runs_and_survive_a_round_trip() {
+        let mut replay = Replay::new(7, 2);
+        replay.bots = vec![Difficulty::Hard];
+        replay.mode = GameMode::Survival;
+        let config = GameConfig::default();
+        let frames = [
+            [input(0.5, false), input(0.0, false)],
+            [input(0.5, false), input(0.0, false)],
+            [input(0.5, true), input(0.0, false)],
+            [input(-1.0, true), input(0.25, true)],
+        ];
+        let ticks = [3, 2, 1, 4];
+        for (frame, &ticks) in frames.iter().zip(&ticks) {
+            for _ in 0..ticks {
+                replay.push(&config, frame);
+            }
+        }
+        // The first two frames are the same and merge into one run.
+        assert_eq!(replay.ends, vec![5, 6, 10]);
+        assert_eq!(replay.len(), 10);
+        assert_eq!(replay.input(4), Some(&frames[1][..]));
+        assert_eq!(replay.input(5), Some(&frames[2][..]));
+        assert_eq!(replay.input(9), Some(&frames[3][..]));
+        assert_eq!(replay.input(10), None);
+
+        let read = round_trip(&replay);
+        assert_eq!(read, replay);
+        assert_eq!(read.players(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "persistence")]
+    fn config_changes_survive_a_round_trip() {
+        let mut replay = Replay::new(7, 1);
+        let mut config = GameConfig::default();
+        replay.push(&config, &[Input::default()]);
+        config.physics.gravity *= 2.0;
+        replay.push(&config, &[Input::default()]);
+        replay.push(&config, &[Input::default()]);
+        config.player.ability_strip_radius = 7.0;
+        replay.push(&config, &[Input::default()]);
+
+        let read = round_trip(&replay);
+        assert_eq!(read, replay);
+        assert_eq!(read.config_at(0), GameConfig::default());
+        assert_eq!(read.config_change(0), None);
+        assert_eq!(
+            read.config_change(1).map(|c| c.physics.gravity),
+            Some(config.physics.gravity)
+        );
+        assert_eq!(read.config_change(2), None);
+        assert_eq!(read.config_at(3), config);
+    }
+
+    #[test]
+    fn runs_longer_than_a_count_stay_encoded() {
+        let mut replay = Replay::new(7, 1);
+        replay.push(&GameConfig::default(), &[input(1.0, true)]);
+        let ticks = u32::MAX as u64 * 2 + 5;
+        replay.ends = vec![ticks];
+
+        let read = round_trip(&replay);
+        assert_eq!(read.ends.last(), Some(&ticks));
+        assert_eq!(read.inputs.len(), 3);
+        assert_eq!(
+            read.input(u32::MAX as usize * 2),
+            Some(&[input(1.0, true)][..])
+        );
+    }
+
+    #[test]
+    fn other_formats_are_refused() {
+        let mut file = vec![];
+        Replay::new(7, 1).write_to(&mut file).unwrap();
+        file[MAGIC.len()] = FORMAT + 1;
+        assert!(Replay::read_from(&file[..]).is_err());
+    }
+}
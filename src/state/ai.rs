@@ -0,0 +1,192 @@
+use super::space::NeighbourQuery;
+use super::{hue_distance, Color, GameConfig, IsPlayer, PhysicsConfig, Player, Position, Speed};
+use float_ord::FloatOrd;
+use glam::DVec2;
+use hecs::World;
+
+/// World units around a bot in which it sees free dots and cores.
+const SIGHT: f64 = 12.0;
+/// How much more a blackhole matters than a single free dot.
+const FLEE_WEIGHT: f64 = 20.0;
+
+/// How well an AI rival plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+    /// Ticks between two decisions.
+    fn reaction_ticks(self) -> u32 {
+        match self {
+            Difficulty::Easy => 40,
+            Difficulty::Normal => 20,
+            Difficulty::Hard => 6,
+        }
+    }
+    /// Directions a bot weighs before picking one.
+    fn headings(self) -> usize {
+        match self {
+            Difficulty::Easy => 6,
+            Difficulty::Normal => 12,
+            Difficulty::Hard => 24,
+        }
+    }
+    /// Ticks ahead a bot predicts where dots and cores are going.
+    fn lookahead(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.0,
+            Difficulty::Normal => 15.0,
+            Difficulty::Hard => 40.0,
+        }
+    }
+}
+
+/// Core steered by the AI, it holds a direction like a player holds a key.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Bot {
+    pub difficulty: Difficulty,
+    dir: DVec2,
+    /// Ticks until the next decision.
+    cooldown: u32,
+}
+
+impl Bot {
+    /// `stagger` spreads decisions of several bots over different ticks.
+    pub fn new(difficulty: Difficulty, stagger: u32) -> Self {
+        Self {
+            difficulty,
+            dir: DVec2::ZERO,
+            cooldown: stagger % difficulty.reaction_ticks(),
+        }
+    }
+}
+
+/// Applies the held direction of every bot, like `State::player_input` does for players.
+pub(super) fn steer(world: &mut World, config: &GameConfig) {
+    let impulse = config.player.input_impulse;
+    for (_, (bot, speed)) in world.query_mut::<(&Bot, &mut Speed)>() {
+        speed.vec += bot.dir * impulse;
+    }
+}
+
+/// Bots whose reaction time is up pick a new direction: toward free dots of their orbiting hues,
+/// away from repelling colors and from blackholes bigger than them.
+pub(super) fn think(world: &World, config: &GameConfig, index: &impl NeighbourQuery) {
+    let cores: Vec<_> = world
+        .query::<(&Player, &Position, &Speed)>()
+        .iter()
+        .map(|(entity, (player, pos, speed))| (entity, pos.vec, speed.vec, player.energy_size()))
+        .collect();
+    let physics = &config.physics;
+
+    for (entity, (bot, player, pos, speed)) in world
+        .query::<(&mut Bot, &Player, &Position, &Speed)>()
+        .iter()
+    {
+        if bot.cooldown > 0 {
+            bot.cooldown -= 1;
+            continue;
+        }
+        bot.cooldown = bot.difficulty.reaction_ticks();
+        let lookahead = bot.difficulty.lookahead();
+        let me = pos.vec + speed.vec * lookahead;
+        let hues: Vec<_> = player
+            .dots
+            .iter()
+            .filter_map(|&dot| Some(world.get::<Color>(dot).ok()?.hsl.hue()))
+            .collect();
+        let headings = bot.difficulty.headings();
+        let heading = |i: usize| {
+            let angle = std::f64::consts::TAU * i as f64 / headings as f64;
+            DVec2::new(angle.cos(), angle.sin())
+        };
+        // Only what lies ahead counts for a heading.
+        let mut scores = vec![0.0; headings];
+        let mut score = |offset: DVec2, weight: f64| {
+            let dist = offset.length();
+            let normal = offset.normalize_or_zero();
+            for (i, score) in scores.iter_mut().enumerate() {
+                *score += weight * normal.dot(heading(i)).max(0.0) / (1.0 + dist);
+            }
+        };
+
+        index.for_each_neighbour(me, SIGHT, |other| {
+            let query = match world.query_one::<(&Color, &Speed)>(other.entity) {
+                Ok(query) => query,
+                Err(_) => return,
+            };
+            let mut query = query.without::<IsPlayer>().without::<Player>();
+            let (color, dot_speed) = match query.get() {
+                Some(dot) => dot,
+                None => return,
+            };
+            let hue = color.hsl.hue();
+            let diff = hues
+                .iter()
+                .map(|&own| hue_distance(own, hue))
+                .fold(180.0, f64::min);
+            // Same sign as the pull between the dot and our closest orbiting dot.
+            let attraction = 1.0 - (diff + physics.hue_offset) / physics.hue_falloff;
+            let weight = attraction * color.hsl.saturation() / 100.0;
+            score(other.diff + dot_speed.vec * lookahead, weight);
+        });
+
+        let size = player.energy_size();
+        for &(other, other_pos, other_speed, other_size) in &cores {
+            if other == entity || !config.player.is_blackhole(other_size) || other_size <= size {
+                continue;
+            }
+            let offset = other_pos + other_speed * lookahead - me;
+            if offset.length() < SIGHT {
+                score(offset, -FLEE_WEIGHT);
+            }
+        }
+        let best = scores
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, score)| FloatOrd(**score))
+            .filter(|(_, score)| **score > 0.0)
+            .map(|(i, _)| i);
+        bot.dir = match best {
+            Some(i) => heading(i),
+            // Nothing worth it in sight, go for the closest dot it can take anywhere.
+            None => nearest_attracting(world, physics, me, &hues)
+                .map_or(DVec2::ZERO, |dot| (dot - me).normalize_or_zero()),
+        };
+    }
+}
+
+/// Position of the closest free dot which is pulled by one of `hues`.
+fn nearest_attracting(
+    world: &World,
+    physics: &PhysicsConfig,
+    me: DVec2,
+    hues: &[f64],
+) -> Option<DVec2> {
+    world
+        .query::<(&Color, &Position)>()
+        .without::<IsPlayer>()
+        .without::<Player>()
+        .iter()
+        .filter(|(_, (color, _))| {
+            let hue = color.hsl.hue();
+            hues.iter().any(|&own| physics.attracts(own, hue))
+        })
+        .map(|(_, (_, pos))| pos.vec)
+        .min_by_key(|pos| FloatOrd(pos.distance_squared(me)))
+}
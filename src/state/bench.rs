@@ -0,0 +1,82 @@
+//! Entry points into private systems for `benches/`, not a stable API.
+use super::space::{NeighbourQuery, Space};
+use super::{Color, IsPlayer, Player, Position, State};
+use bumpalo::Bump;
+use glam::DVec2;
+
+/// New game with `dots` free dots, spread so their density is the same as in a normal game.
+pub fn state(seed: u64, dots: usize) -> State {
+    let mut state = State::with_seed(seed);
+    state.spawn_dots_within(dots, 10.0 * (dots as f64 / 1000.0).sqrt());
+    state.spawn_player(DVec2::ZERO);
+    state
+}
+
+fn radius(state: &State) -> f64 {
+    state
+        .config
+        .physics
+        .neighbour_radius(state.world.len() as usize)
+}
+
+/// Partitions positions of every entity, returns the number of rects.
+pub fn partition(state: &State, bump: &mut Bump) -> usize {
+    let rects = Space::build(&state.world, bump).bounds().count();
+    bump.reset();
+    rects
+}
+
+/// Partition built once, so only the queries are measured.
+pub struct Neighbours<'a> {
+    space: Space<'a>,
+    positions: Vec<DVec2>,
+    radius: f64,
+}
+impl<'a> Neighbours<'a> {
+    pub fn new(state: &State, bump: &'a Bump) -> Self {
+        let mut query = state.world.query::<&Position>();
+        Self {
+            space: Space::build(&state.world, bump),
+            positions: query.iter().map(|(_, pos)| pos.vec).collect(),
+            radius: radius(state),
+        }
+    }
+    /// Searches around every entity, returns the total number of neighbours found.
+    pub fn run(&self) -> usize {
+        let mut found = 0;
+        for &pos in &self.positions {
+            self.space
+                .for_each_neighbour(pos, self.radius, |_| found += 1);
+        }
+        found
+    }
+}
+
+/// Brings the grid up to date, `attract` expects it.
+pub fn index(state: &mut State) {
+    let radius = radius(state);
+    state.grid.update(&mut state.world, radius);
+}
+
+pub fn attract(state: &State) {
+    let radius = radius(state);
+    super::attract(&state.world, &state.config.physics, &state.grid, radius);
+}
+
+/// Drains all saturation from every `nth` free dot, so `heat_death` has work to do.
+pub fn desaturate(state: &mut State, nth: usize) {
+    for (_, color) in state
+        .world
+        .query_mut::<&mut Color>()
+        .without::<Player>()
+        .without::<IsPlayer>()
+        .into_iter()
+        .step_by(nth)
+    {
+        color.hsl.set_saturation(0.0);
+    }
+}
+
+pub fn heat_death(state: &mut State) {
+    super::heat_death(state);
+}
@@ -0,0 +1,163 @@
+/// Tuning of the simulation, owned by `State`.
+/// Can be loaded from a RON file, missing fields keep their default values.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GameConfig {
+    pub physics: PhysicsConfig,
+    pub player: PlayerConfig,
+    pub round: RoundConfig,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PhysicsConfig {
+    /// Pull toward the center of the world, grows with squared distance.
+    pub gravity: f64,
+    /// Speed is multiplied by this every tick.
+    pub deceleration: f64,
+    /// Added to the hue difference of two dots before the falloff.
+    pub hue_offset: f64,
+    /// Hue difference (with offset) at which attraction turns into repulsion.
+    pub hue_falloff: f64,
+    /// Dots closer than this always push each other apart.
+    pub repulsion_radius: f64,
+    pub repulsion_force: f64,
+    pub attraction_force: f64,
+    /// Neighbour search radius is this divided by the number of dots.
+    pub neighbour_budget: f64,
+    pub min_neighbour_radius: f64,
+    pub spatial_index: SpatialIndex,
+}
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            gravity: 0.001,
+            deceleration: 0.9,
+            hue_offset: 54.0,
+            hue_falloff: 90.0,
+            repulsion_radius: 0.3,
+            repulsion_force: 0.0001,
+            attraction_force: 0.01,
+            neighbour_budget: 10000.0,
+            min_neighbour_radius: 10.0,
+            spatial_index: SpatialIndex::default(),
+        }
+    }
+}
+impl PhysicsConfig {
+    pub fn neighbour_radius(&self, count: usize) -> f64 {
+        let max = self.neighbour_budget.max(self.min_neighbour_radius);
+        (self.neighbour_budget / count as f64).clamp(self.min_neighbour_radius, max)
+    }
+    /// Dots of these hues pull each other in rather than push away.
+    pub fn attracts(&self, hue: f64, other: f64) -> bool {
+        super::hue_distance(hue, other) + self.hue_offset < self.hue_falloff
+    }
+}
+
+/// How dots find their neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpatialIndex {
+    /// Uniform grid kept between ticks, cells sized by the neighbour radius.
+    #[default]
+    Grid,
+    /// Space partition rebuilt from scratch every tick.
+    Partition,
+}
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PlayerConfig {
+    /// Speed added per tick at full input.
+    pub input_impulse: f64,
+    /// Turns of the orbiting dots per tick.
+    pub rotation_speed: f64,
+    /// Orbiting dots drain free dots within this distance.
+    pub consume_radius: f64,
+    /// Saturation taken from a free dot per tick.
+    pub saturation_drain: f64,
+    /// Energy gained per drained dot per tick.
+    pub energy_per_drain: f64,
+    /// Core collapses into a blackhole beyond this size.
+    pub blackhole_size: f64,
+    /// Blackhole strips orbiting dots of smaller cores within this distance of its core.
+    pub strip_radius: f64,
+    /// Strip radius of a blackhole whose player holds the ability button.
+    pub ability_strip_radius: f64,
+    /// Ticks before a core can lose another orbiting dot to a blackhole.
+    pub strip_cooldown: u32,
+    /// Orbiting dots of rivals which differ less than this in hue repel each other's cores.
+    pub rival_hue_range: f64,
+    pub rival_repulsion_radius: f64,
+    /// Speed added per tick to a core by a matching rival dot right next to its own.
+    pub rival_repulsion_force: f64,
+}
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            input_impulse: 0.01,
+            rotation_speed: 0.001,
+            consume_radius: 0.2,
+            saturation_drain: 0.03,
+            energy_per_drain: 0.0003,
+            blackhole_size: 9.0,
+            strip_radius: 1.5,
+            ability_strip_radius: 3.0,
+            strip_cooldown: 30,
+            rival_hue_range: 30.0,
+            rival_repulsion_radius: 1.0,
+            rival_repulsion_force: 0.005,
+        }
+    }
+}
+/// Rules of the game modes.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RoundConfig {
+    /// Length of a Time Attack round.
+    pub time_attack_ticks: u32,
+    /// Energy every core burns per tick in Survival.
+    pub survival_upkeep: f64,
+    /// Core is out of Survival once its energy size falls below this.
+    pub survival_min_size: f64,
+}
+impl Default for RoundConfig {
+    fn default() -> Self {
+        Self {
+            time_attack_ticks: 3 * 60 * 60,
+            survival_upkeep: 0.0002,
+            survival_min_size: 0.5,
+        }
+    }
+}
+impl PlayerConfig {
+    pub fn is_blackhole(&self, size: f64) -> bool {
+        size > self.blackhole_size
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl GameConfig {
+    pub fn from_ron(ron: &str) -> Result<Self, ron::Error> {
+        ron::from_str(ron)
+    }
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, Default::default())
+    }
+}
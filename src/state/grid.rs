@@ -0,0 +1,190 @@
+use super::space::{Bounds, Neighbour, NeighbourQuery};
+use super::Position;
+use glam::DVec2;
+use hecs::{Entity, World};
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+type Key = (i32, i32);
+
+/// Cells are looked up for every query, SipHash would dominate the search.
+#[derive(Default)]
+struct KeyHasher(u64);
+impl Hasher for KeyHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u32(byte as u32);
+        }
+    }
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+    fn write_u32(&mut self, i: u32) {
+        self.0 = (self.0.rotate_left(5) ^ i as u64).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Where in the `Grid` the entity is stored, kept as a component of the entity.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct GridCell {
+    key: Key,
+    index: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Element {
+    entity: Entity,
+    pos: DVec2,
+}
+
+/// Uniform grid which lives between ticks, an entity only moves to
+/// another cell when it crosses a cell border.
+#[derive(Debug, Default)]
+pub(super) struct Grid {
+    cell_size: f64,
+    cells: HashMap<Key, Vec<Element>, BuildHasherDefault<KeyHasher>>,
+}
+
+impl Grid {
+    fn key(&self, pos: DVec2) -> Key {
+        let cell = (pos / self.cell_size).floor();
+        (cell.x as i32, cell.y as i32)
+    }
+    fn cell_bounds(&self, key: Key) -> Bounds {
+        let min = DVec2::new(key.0 as f64, key.1 as f64) * self.cell_size;
+        Bounds {
+            min,
+            max: min + DVec2::splat(self.cell_size),
+        }
+    }
+    fn push(&mut self, entity: Entity, pos: DVec2) -> GridCell {
+        let key = self.key(pos);
+        let elements = self.cells.entry(key).or_default();
+        elements.push(Element { entity, pos });
+        GridCell {
+            key,
+            index: elements.len() - 1,
+        }
+    }
+    /// Takes the entity out of its cell, its components are left as is.
+    pub fn unindex(&mut self, world: &World, entity: Entity) {
+        let cell = world.get::<GridCell>(entity).ok().map(|cell| *cell);
+        if let Some(cell) = cell {
+            self.remove(world, cell);
+        }
+    }
+    /// Every despawn goes through here, or the grid keeps pointing at dead entities.
+    pub fn despawn(&mut self, world: &mut World, entity: Entity) {
+        self.unindex(world, entity);
+        let _ = world.despawn(entity);
+    }
+    fn remove(&mut self, world: &World, cell: GridCell) {
+        let elements = match self.cells.get_mut(&cell.key) {
+            Some(elements) => elements,
+            None => return,
+        };
+        elements.swap_remove(cell.index);
+        if let Some(swapped) = elements.get(cell.index) {
+            if let Ok(mut swapped) = world.get_mut::<GridCell>(swapped.entity) {
+                swapped.index = cell.index;
+            }
+        }
+        if elements.is_empty() {
+            self.cells.remove(&cell.key);
+        }
+    }
+    /// Brings the grid up to date with positions of all entities.
+    /// Cells are as large as the power of two nearest to `cell_size` and only rebuilt when that changes.
+    /// Neither the cells nor the order within them depend on how the world got here,
+    /// so a loaded game finds neighbours in the same order as the saved one.
+    pub fn update(&mut self, world: &mut World, cell_size: f64) {
+        let cell_size = cell_size.log2().round().exp2();
+        let rebuild = cell_size != self.cell_size;
+        if rebuild {
+            self.cell_size = cell_size;
+            self.cells.clear();
+        }
+        let mut moved = vec![];
+        let mut added = vec![];
+        for (entity, (pos, cell)) in world.query_mut::<(&Position, Option<&mut GridCell>)>() {
+            match cell {
+                Some(cell) if rebuild => *cell = self.push(entity, pos.vec),
+                Some(cell) if cell.key == self.key(pos.vec) => {
+                    self.cells.get_mut(&cell.key).expect("cell of an entity")[cell.index].pos =
+                        pos.vec;
+                }
+                Some(_) => moved.push((entity, pos.vec)),
+                None => added.push((entity, pos.vec)),
+            }
+        }
+        for (entity, pos) in moved {
+            let cell = *world.get::<GridCell>(entity).expect("moved entity");
+            self.remove(world, cell);
+            let cell = self.push(entity, pos);
+            *world.get_mut::<GridCell>(entity).expect("moved entity") = cell;
+        }
+        for (entity, pos) in added {
+            let cell = self.push(entity, pos);
+            let _ = world.insert_one(entity, cell);
+        }
+        // Removals swap elements around, sorting by position undoes the history.
+        for elements in self.cells.values_mut() {
+            elements.sort_unstable_by(|a, b| {
+                a.pos
+                    .x
+                    .total_cmp(&b.pos.x)
+                    .then(a.pos.y.total_cmp(&b.pos.y))
+            });
+            for (index, element) in elements.iter().enumerate() {
+                if let Ok(mut cell) = world.get_mut::<GridCell>(element.entity) {
+                    cell.index = index;
+                }
+            }
+        }
+    }
+    /// Bounds of every non-empty cell.
+    pub fn bounds(&self) -> impl '_ + Iterator<Item = Bounds> {
+        self.cells.keys().map(move |&key| self.cell_bounds(key))
+    }
+}
+
+impl NeighbourQuery for Grid {
+    fn for_each_neighbour(&self, pos: DVec2, dist: f64, mut f: impl FnMut(Neighbour)) {
+        if self.cells.is_empty() {
+            return;
+        }
+        let min = self.key(pos - DVec2::splat(dist));
+        let max = self.key(pos + DVec2::splat(dist));
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                let elements = match self.cells.get(&(x, y)) {
+                    Some(elements) => elements,
+                    None => continue,
+                };
+                for el in elements {
+                    let diff = el.pos - pos;
+                    let dist_to = diff.length();
+                    if dist_to <= dist {
+                        f(Neighbour {
+                            entity: el.entity,
+                            diff,
+                            dist: dist_to,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    fn map_queries<T: Sync, R: Send>(
+        &self,
+        items: &[T],
+        f: impl Fn(&Self, &T) -> R + Sync,
+    ) -> Vec<R> {
+        use rayon::prelude::*;
+        items.par_iter().map(|item| f(self, item)).collect()
+    }
+}
@@ -1,30 +1,88 @@
+use ai::Bot;
 use bumpalo::{collections::Vec as BumpVec, Bump};
 use colorsys::{Hsl, Rgb};
+use float_ord::FloatOrd;
 use glam::DVec2;
+use grid::Grid;
 use hecs::{Entity, World};
-use rand::{prelude::ThreadRng, Rng};
-use space::{Neighbour, Neighbours};
-
+use instant::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use round::Round;
+use score::Drain;
+use space::{Neighbour, NeighbourQuery, Space};
+
+pub use space::Bounds;
+
+pub use ai::Difficulty;
+pub use config::{GameConfig, PhysicsConfig, PlayerConfig, RoundConfig, SpatialIndex};
+pub use round::{GameMode, Results, Standing};
+#[cfg(feature = "persistence")]
+pub use save::{Remap, SavedEntity, SavedState};
+pub use score::{Score, HUES};
+
+mod ai;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+mod config;
+mod grid;
+mod round;
+#[cfg(feature = "persistence")]
+mod save;
+mod score;
 mod space;
+#[cfg(test)]
+mod tests;
 
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub vec: DVec2,
 }
 
+impl Position {
+    pub fn lerp(self, next: Position, alpha: f64) -> Position {
+        Position {
+            vec: self.vec.lerp(next.vec, alpha),
+        }
+    }
+}
+
+/// Position at the start of the current tick, for interpolation between ticks.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct LastPosition(Position);
+
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size(pub f64);
 
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Speed {
     vec: DVec2,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "save::SavedColor", into = "save::SavedColor")
+)]
 pub struct Color {
     hsl: Hsl,
 }
 impl Color {
+    pub fn hue(&self) -> f64 {
+        self.hsl.hue()
+    }
+    pub fn saturation(&self) -> f64 {
+        self.hsl.saturation()
+    }
+    pub fn lightness(&self) -> f64 {
+        self.hsl.lightness()
+    }
     fn bytes(&self) -> [u8; 3] {
         let rgb = Rgb::from(&self.hsl);
         rgb.into()
@@ -53,12 +111,17 @@ impl From<Hsl> for Color {
     }
 }
 */
-struct IsPlayer(Entity);
+struct IsPlayer(#[allow(dead_code)] Entity);
 
 struct Player {
     dots: Vec<Entity>,
     energy: f64,
     rot: f64,
+    /// Ticks until a blackhole can strip another orbiting dot.
+    immunity: u32,
+    /// Ability button is held, a blackhole reaches further.
+    ability: bool,
+    drain: Drain,
 }
 impl Player {
     fn new() -> Self {
@@ -67,6 +130,9 @@ impl Player {
             //energy: 8.7f64.powf(2.0),
             energy: 1.0,
             rot: 0.0,
+            immunity: 0,
+            ability: false,
+            drain: Drain::default(),
         }
     }
     fn update(state: &mut State) {
@@ -82,44 +148,58 @@ impl Player {
         }
     }
     fn rotate(state: &mut State) {
+        let rotation_speed = state.config.player.rotation_speed;
         for (_, player) in state.world.query::<&mut Player>().iter() {
-            player.rot += 0.001;
+            player.rot += rotation_speed;
         }
     }
-    fn consume_around_dot(&self, world: &World, dot: Entity, dist: f64) -> Option<f64> {
-        let neighbours = world.get::<Neighbours>(dot).ok()?;
+    fn consume_around_dot(
+        &self,
+        world: &World,
+        config: &PlayerConfig,
+        index: &impl NeighbourQuery,
+        dot: Entity,
+        drain: &mut Drain,
+    ) -> Option<f64> {
+        let pos = world.get::<Position>(dot).ok()?.vec;
         let energy_size = self.energy_size();
-        let rate = if Player::is_blackhole(energy_size) {
+        let dist = config.consume_radius;
+        let rate = if config.is_blackhole(energy_size) {
             energy_size
         } else {
             1.0
         };
 
         let mut energy = 0.0;
-        for other in neighbours.slice() {
-            if other.dist > dist * rate {
-                continue;
-            }
-            if let Some(query) = world.query_one::<&mut Color>(other.entity).ok() {
+        index.for_each_neighbour(pos, dist * rate, |other| {
+            if let Ok(query) = world.query_one::<&mut Color>(other.entity) {
                 if let Some(color) = query.without::<IsPlayer>().without::<Player>().get() {
                     let sat = color.hsl.saturation();
                     if sat > 0.0 {
-                        let sat = (sat - 0.03 * rate * rate).max(0.0);
-                        color.hsl.set_saturation(sat);
-                        energy += 0.0003 * rate;
+                        let new_sat = (sat - config.saturation_drain * rate * rate).max(0.0);
+                        color.hsl.set_saturation(new_sat);
+                        energy += config.energy_per_drain * rate;
+                        drain.add(color.hsl.hue(), config.energy_per_drain * rate);
+                        if sat > FADED && new_sat <= FADED {
+                            drain.dots += 1;
+                        }
                     }
                 }
             }
-        }
+        });
         Some(energy)
     }
-    fn consume_energy(state: &mut State) {
-        for (_entity, (player,)) in state.world.query::<(&mut Player,)>().iter() {
+    fn consume_energy(world: &World, config: &PlayerConfig, index: &impl NeighbourQuery) {
+        for (_entity, (player,)) in world.query::<(&mut Player,)>().iter() {
+            let mut drain = std::mem::take(&mut player.drain);
             for dot in &player.dots {
-                player.energy += player
-                    .consume_around_dot(&state.world, *dot, 0.2)
+                let energy = player
+                    .consume_around_dot(world, config, index, *dot, &mut drain)
                     .unwrap_or(0.0);
+                player.energy += energy;
+                drain.energy += energy;
             }
+            player.drain = drain;
         }
     }
     fn update_dot_position(&self, world: &World, i: usize, pos: DVec2, size: f64) -> Option<()> {
@@ -145,9 +225,9 @@ impl Player {
             hsl: Hsl::default(),
         };
         let pos = Position::default();
+        let last_pos = LastPosition(pos);
         let is_player = IsPlayer(player);
-        let neighbours = Neighbours::default();
-        world.spawn_at(*dots.last().unwrap(), (color, pos, is_player, neighbours));
+        world.spawn_at(*dots.last().unwrap(), (color, pos, last_pos, is_player));
 
         let angle = (std::f64::consts::TAU / dots.len() as f64).to_degrees();
 
@@ -164,10 +244,8 @@ impl Player {
     fn energy_size(&self) -> f64 {
         self.energy.powf(0.5)
     }
-    fn is_blackhole(size: f64) -> bool {
-        size > 9.0
-    }
     fn grow(state: &mut State) {
+        let config = &state.config.player;
         let mut add_dots = vec![];
         for (entity, (player, size, color)) in state
             .world
@@ -175,14 +253,14 @@ impl Player {
             .iter()
         {
             let new_size = player.energy_size();
-            if Player::is_blackhole(new_size) {
+            if config.is_blackhole(new_size) {
                 size.0 = (size.0 - 0.1).max(0.01);
-                color.hsl.set_lightness((size.0 - 1.0) / 8.0 * 100.0);
+                let lightness = (size.0 - 1.0) / (config.blackhole_size - 1.0);
+                color.hsl.set_lightness(lightness * 100.0);
                 player.set_dots_lightness(&state.world, color.hsl.lightness());
             } else {
-                if new_size > size.0 {
-                    size.0 = new_size;
-                }
+                // Only Survival takes energy away, cores shrink with it.
+                size.0 = new_size;
                 if new_size >= (player.dots.len() + 1) as f64 {
                     add_dots.push(entity);
                 }
@@ -192,6 +270,114 @@ impl Player {
             Self::add_dot(&mut state.world, player);
         }
     }
+    /// Blackholes release orbiting dots of smaller cores as free dots,
+    /// a core left without orbiting dots is eliminated.
+    /// Holding the ability stretches the reach of a blackhole.
+    /// Zen has no winners, there a core keeps its last orbiting dot and stays in the game.
+    fn strip(state: &mut State) {
+        let config = &state.config.player;
+        let kept = match state.round.mode {
+            GameMode::Zen => 1,
+            GameMode::TimeAttack | GameMode::Survival | GameMode::BlackholeRace => 0,
+        };
+        let world = &state.world;
+        let mut cores = vec![];
+        for (entity, (player, pos)) in world.query::<(&mut Player, &Position)>().iter() {
+            player.immunity = player.immunity.saturating_sub(1);
+            let reach = if player.ability {
+                config.ability_strip_radius
+            } else {
+                config.strip_radius
+            };
+            cores.push((entity, pos.vec, player.energy_size(), reach));
+        }
+        let mut released = vec![];
+        for &(blackhole, pos, size, reach) in &cores {
+            if !config.is_blackhole(size) {
+                continue;
+            }
+            for &(victim, _, victim_size, _) in &cores {
+                if victim == blackhole || victim_size >= size {
+                    continue;
+                }
+                let mut player = match world.get_mut::<Player>(victim) {
+                    Ok(player) if player.immunity == 0 && player.dots.len() > kept => player,
+                    _ => continue,
+                };
+                let nearest = player
+                    .dots
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, &dot)| {
+                        let dist = world.get::<Position>(dot).ok()?.vec.distance(pos);
+                        Some((i, FloatOrd(dist)))
+                    })
+                    .filter(|(_, dist)| dist.0 <= reach)
+                    .min_by_key(|(_, dist)| *dist);
+                if let Some((i, _)) = nearest {
+                    released.push(player.dots.remove(i));
+                    player.immunity = config.strip_cooldown;
+                }
+            }
+        }
+        for dot in released {
+            let _ = state.world.remove_one::<IsPlayer>(dot);
+            let _ = state.world.insert_one(dot, Speed::default());
+        }
+
+        let eliminated: Vec<_> = state
+            .world
+            .query::<&Player>()
+            .iter()
+            .filter(|(_, player)| player.dots.is_empty())
+            .map(|(entity, _)| entity)
+            .collect();
+        for player in eliminated {
+            state.grid.despawn(&mut state.world, player);
+        }
+    }
+    /// Orbiting dots of matching hue push rival cores away from each other.
+    fn repel_rivals(state: &mut State) {
+        let config = &state.config.player;
+        let world = &state.world;
+        let orbits: Vec<_> = world
+            .query::<&Player>()
+            .iter()
+            .map(|(entity, player)| {
+                let dots: Vec<_> = player
+                    .dots
+                    .iter()
+                    .filter_map(|&dot| {
+                        let pos = world.get::<Position>(dot).ok()?.vec;
+                        let hue = world.get::<Color>(dot).ok()?.hsl.hue();
+                        Some((pos, hue))
+                    })
+                    .collect();
+                (entity, dots)
+            })
+            .collect();
+        for (core, dots) in &orbits {
+            let mut push = DVec2::ZERO;
+            for (_, rival_dots) in orbits.iter().filter(|(rival, _)| rival != core) {
+                for &(pos, hue) in dots {
+                    for &(rival_pos, rival_hue) in rival_dots {
+                        if hue_distance(hue, rival_hue) >= config.rival_hue_range {
+                            continue;
+                        }
+                        let diff = pos - rival_pos;
+                        let dist = diff.length();
+                        if dist < config.rival_repulsion_radius {
+                            push += diff.normalize_or_zero()
+                                * (1.0 - dist / config.rival_repulsion_radius);
+                        }
+                    }
+                }
+            }
+            if let Ok(mut speed) = world.get_mut::<Speed>(*core) {
+                speed.vec += push * config.rival_repulsion_force;
+            }
+        }
+    }
     fn set_dots_lightness(&self, world: &World, lightness: f64) {
         for dot in &self.dots {
             if let Ok(mut color) = world.get_mut::<Color>(*dot) {
@@ -201,17 +387,34 @@ impl Player {
     }
 }
 
-#[derive(Default)]
 pub struct State {
     world: World,
     bump: Bump,
+    random: Random,
+    seed: u64,
+    config: GameConfig,
+    stats: TickStats,
+    grid: Grid,
+    partition: Vec<Bounds>,
+    round: Round,
+}
+impl Default for State {
+    fn default() -> Self {
+        Self::with_seed(rand::random())
+    }
 }
 
-#[derive(Default)]
+/// Every random decision of the simulation goes through this,
+/// so the same seed and the same inputs always give the same world.
 struct Random {
-    rng: ThreadRng,
+    rng: Pcg64Mcg,
 }
 impl Random {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: Pcg64Mcg::seed_from_u64(seed),
+        }
+    }
     fn dvec2(&mut self, amp: f64) -> DVec2 {
         DVec2::new(self.rng.gen_range(-amp..amp), self.rng.gen_range(-amp..amp))
     }
@@ -228,9 +431,16 @@ impl Random {
 }
 
 fn global_gravity(state: &mut State) {
+    let gravity = state.config.physics.gravity;
     for (_, (pos, speed)) in state.world.query_mut::<(&Position, &mut Speed)>() {
         let dist = pos.vec.length();
-        speed.vec -= pos.vec.normalize_or_zero() * (dist * 0.001).powf(2.0);
+        speed.vec -= pos.vec.normalize_or_zero() * (dist * gravity).powf(2.0);
+    }
+}
+
+fn remember_positions(state: &mut State) {
+    for (_, (pos, last_pos)) in state.world.query_mut::<(&Position, &mut LastPosition)>() {
+        last_pos.0 = *pos;
     }
 }
 
@@ -241,26 +451,58 @@ fn position_speed(state: &mut State) {
 }
 
 fn decelerate(state: &mut State) {
+    let deceleration = state.config.physics.deceleration;
     for (_, speed) in state.world.query_mut::<&mut Speed>() {
-        speed.vec *= 0.9;
+        speed.vec *= deceleration;
     }
 }
 
-fn neighbour_attraction(color: &Color, world: &World, other: &Neighbour) -> Option<DVec2> {
-    let normal = other.diff.try_normalize()?;
-    if other.dist < 0.3 {
-        return Some(-normal / other.dist.max(0.000001) * 0.0001);
+/// Degrees between two hues around the color wheel, in `0.0..=180.0`.
+fn hue_distance(hue: f64, other: f64) -> f64 {
+    let diff = (hue - other).abs();
+    if diff > 180.0 {
+        360.0 - diff
+    } else {
+        diff
     }
+}
 
-    let mut query = world.query_one::<&Color>(other.entity).ok()?;
-    let other_color = query.get()?;
+/// Color of every entity by its id, taken once a tick.
+/// Asking the world for the color of every neighbour took a third of the tick.
+struct Colors(Vec<Option<Color>>);
+
+impl Colors {
+    fn new(world: &World) -> Self {
+        let mut colors = vec![];
+        for (entity, color) in world.query::<&Color>().iter() {
+            let id = entity.id() as usize;
+            if colors.len() <= id {
+                colors.resize(id + 1, None);
+            }
+            colors[id] = Some(color.clone());
+        }
+        Self(colors)
+    }
+    fn get(&self, entity: Entity) -> Option<&Color> {
+        self.0.get(entity.id() as usize)?.as_ref()
+    }
+}
 
-    let mut color_diff = (color.hsl.hue() - other_color.hsl.hue()).abs();
-    if color_diff > 180.0 {
-        color_diff = 360.0 - color_diff;
+fn neighbour_attraction(
+    color: &Color,
+    colors: &Colors,
+    config: &PhysicsConfig,
+    other: &Neighbour,
+) -> Option<DVec2> {
+    let normal = other.diff.try_normalize()?;
+    if other.dist < config.repulsion_radius {
+        return Some(-normal / other.dist.max(0.000001) * config.repulsion_force);
     }
-    color_diff = color_diff + 54.0; //(360.0 / DOTS_NUMBER as f64 * 0.75);
-    let force = 1.0 - (color_diff / 90.0);
+
+    let other_color = colors.get(other.entity)?;
+
+    let color_diff = hue_distance(color.hsl.hue(), other_color.hsl.hue()) + config.hue_offset; //(360.0 / DOTS_NUMBER as f64 * 0.75);
+    let force = 1.0 - (color_diff / config.hue_falloff);
     let force = force.abs().powf(0.5) * force.signum();
 
     let sunction = 1.0 - other_color.hsl.lightness() / 50.0;
@@ -276,25 +518,38 @@ fn neighbour_attraction(color: &Color, world: &World, other: &Neighbour) -> Opti
     let dist = other.dist * other.dist * other.dist;
     let dist = 1.0 / dist;
 
-    Some(normal * 0.01 * (dist * (power * force) + sunction * 0.3))
+    Some(normal * config.attraction_force * (dist * (power * force) + sunction * 0.3))
 }
 
-fn attract(state: &mut State) {
-    for (_, (color, speed, neighbours)) in state
-        .world
-        .query::<(&Color, &mut Speed, &Neighbours)>()
+/// Forces are summed into a buffer first and applied afterwards,
+/// so speeds come out the same no matter how many threads did the work.
+fn attract(world: &World, config: &PhysicsConfig, index: &impl NeighbourQuery, radius: f64) {
+    let colors = Colors::new(world);
+    let mut query = world
+        .query::<(&Color, &Position, &mut Speed)>()
         .without::<Player>()
-        .without::<IsPlayer>()
+        .without::<IsPlayer>();
+    let mut dots: Vec<_> = query
         .iter()
-    {
-        for other in neighbours.slice() {
-            if let Some(attraction) = neighbour_attraction(color, &state.world, other) {
-                speed.vec += attraction;
+        .map(|(_, (color, pos, speed))| (color, pos.vec, speed))
+        .collect();
+    let forces = index.map_queries(&dots, |index, (color, pos, _)| {
+        let mut force = DVec2::ZERO;
+        index.for_each_neighbour(*pos, radius, |other| {
+            if let Some(attraction) = neighbour_attraction(color, &colors, config, &other) {
+                force += attraction;
             }
-        }
+        });
+        force
+    });
+    for ((_, _, speed), force) in dots.iter_mut().zip(forces) {
+        speed.vec += force;
     }
 }
 
+/// Free dots drained down to this saturation fade away.
+const FADED: f64 = 0.1;
+
 fn heat_death(state: &mut State) {
     let mut despawn = BumpVec::new_in(&state.bump);
     let count = state
@@ -304,56 +559,228 @@ fn heat_death(state: &mut State) {
         .without::<IsPlayer>()
         .iter()
         .map(|(entity, (color,))| {
-            if color.hsl.saturation() <= 0.1 {
+            if color.hsl.saturation() <= FADED {
                 despawn.push(entity);
             }
         })
         .count();
     for entity in despawn {
-        let _ = state.world.despawn(entity);
+        state.grid.despawn(&mut state.world, entity);
     }
     if count < 10 {
-        restart(state);
+        match state.round.mode {
+            GameMode::Zen => restart(state),
+            // Race is over before a blackhole could eat the new dots as fast as they come.
+            GameMode::BlackholeRace => state.batch_spawn_dots(1000),
+            GameMode::TimeAttack | GameMode::Survival => {}
+        }
     }
 }
 
 fn restart(state: &mut State) {
+    state.round.restart();
     {
         let mut despawn = BumpVec::new_in(&state.bump);
         for (dot, _) in state.world.query::<&IsPlayer>().iter() {
             despawn.push(dot);
         }
         for entity in despawn {
-            let _ = state.world.despawn(entity);
+            state.grid.despawn(&mut state.world, entity);
         }
     }
     {
-        let mut players_to_respawn = BumpVec::new_in(&state.bump);
-        for (player, _) in state.world.query::<&Player>().iter() {
-            players_to_respawn.push(player);
-        }
-        for entity in players_to_respawn {
-            state.respawn_player(entity);
+        let mut players_to_respawn: Vec<_> = state
+            .world
+            .query::<&Player>()
+            .iter()
+            .map(|(player, _)| player)
+            .collect();
+        players_to_respawn.sort();
+        let count = players_to_respawn.len();
+        for (i, entity) in players_to_respawn.into_iter().enumerate() {
+            state.respawn_player(entity, spawn_point(i, count));
         }
     }
     state.batch_spawn_dots(1000);
 }
 
+/// Cores of a local game start apart from each other, a single one in the center.
+pub(crate) fn spawn_point(i: usize, count: usize) -> DVec2 {
+    if count <= 1 {
+        return DVec2::ZERO;
+    }
+    let angle = std::f64::consts::TAU * i as f64 / count as f64;
+    DVec2::new(angle.cos(), angle.sin()) * 4.0
+}
+
 impl State {
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            world: World::default(),
+            bump: Bump::default(),
+            random: Random::new(seed),
+            seed,
+            config: GameConfig::default(),
+            stats: TickStats::default(),
+            grid: Grid::default(),
+            partition: Vec::new(),
+            round: Round::default(),
+        }
+    }
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+    /// Changes apply from the next tick.
+    pub fn config_mut(&mut self) -> &mut GameConfig {
+        &mut self.config
+    }
+    /// Fresh world with free dots and a single player in the center.
+    pub fn new_game(seed: u64) -> (Self, Entity) {
+        let (state, players) = Self::new_game_with_players(seed, 1);
+        (state, players[0])
+    }
+    /// Fresh world with free dots and `count` players around the center.
+    pub fn new_game_with_players(seed: u64, count: usize) -> (Self, Vec<Entity>) {
+        Self::new_game_with_bots(seed, count, &[])
+    }
+    /// Like `new_game_with_players`, AI rivals take the places after the players.
+    pub fn new_game_with_bots(
+        seed: u64,
+        players: usize,
+        bots: &[Difficulty],
+    ) -> (Self, Vec<Entity>) {
+        let mut state = Self::with_seed(seed);
+        state.batch_spawn_dots(1000);
+        let count = players + bots.len();
+        let players = (0..players)
+            .map(|i| state.spawn_player(spawn_point(i, count)))
+            .collect();
+        for (i, &difficulty) in bots.iter().enumerate() {
+            let pos = spawn_point(count - bots.len() + i, count);
+            state.spawn_bot(pos, difficulty);
+        }
+        (state, players)
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    pub fn mode(&self) -> GameMode {
+        self.round.mode
+    }
+    /// Meant for a fresh game, the round clock isn't reset.
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.round.mode = mode;
+    }
+    /// Ticks since the round started.
+    pub fn round_ticks(&self) -> u32 {
+        self.round.ticks
+    }
+    /// `Some` once the round is decided, never in Zen.
+    pub fn results(&self) -> Option<&Results> {
+        self.round.results.as_ref()
+    }
+    /// Times Zen started the world over once it was drained.
+    pub fn restarts(&self) -> u32 {
+        self.round.restarts
+    }
+    /// Standings of the world before the last restart.
+    pub fn drained(&self) -> Option<&Results> {
+        self.round.drained.as_ref()
+    }
+    /// Score of the round so far, kept after the core is eliminated.
+    pub fn score(&self, core: Entity) -> Option<&Standing> {
+        self.round.standings.iter().find(|s| s.core == core)
+    }
+
+    /// Does nothing once the round is over.
     pub fn tick(&mut self) {
+        if self.round.results.is_some() {
+            return;
+        }
+        let start = Instant::now();
         self.bump.reset();
+        remember_positions(self);
+        ai::steer(&mut self.world, &self.config);
         heat_death(self);
         global_gravity(self);
         position_speed(self);
         Player::rotate(self);
-        Player::consume_energy(self);
         Player::grow(self);
         Player::update(self);
+        Player::strip(self);
+        Player::repel_rivals(self);
+        round::update(self);
         decelerate(self);
-        let count = self.world.query::<&Neighbours>().iter().count() as f64;
-        Neighbours::update(self, (10000.0 / count).clamp(10.0, 10000.0));
-        attract(self);
+        let radius = self
+            .config
+            .physics
+            .neighbour_radius(self.world.len() as usize);
+        let neighbours_start = Instant::now();
+        self.interact(radius);
+        self.stats.neighbours_time = neighbours_start.elapsed();
         //std::thread::sleep(Duration::from_micros(1000/60));
+        self.stats.tick_time = start.elapsed();
+        self.stats.neighbour_radius = radius;
+        self.stats.cells = self.partition.len();
+    }
+    /// Indexes positions, then runs every system which looks for neighbours.
+    fn interact(&mut self, radius: f64) {
+        self.partition.clear();
+        match self.config.physics.spatial_index {
+            SpatialIndex::Grid => {
+                self.grid.update(&mut self.world, radius);
+                Player::consume_energy(&self.world, &self.config.player, &self.grid);
+                attract(&self.world, &self.config.physics, &self.grid, radius);
+                ai::think(&self.world, &self.config, &self.grid);
+                self.partition.extend(self.grid.bounds());
+            }
+            SpatialIndex::Partition => {
+                let space = Space::build(&self.world, &self.bump);
+                Player::consume_energy(&self.world, &self.config.player, &space);
+                attract(&self.world, &self.config.physics, &space, radius);
+                ai::think(&self.world, &self.config, &space);
+                self.partition.extend(space.bounds());
+            }
+        }
+    }
+    /// Measurements of the last tick.
+    pub fn tick_stats(&self) -> &TickStats {
+        &self.stats
+    }
+    pub fn entity_count(&self) -> usize {
+        self.world.len() as usize
+    }
+    /// Grid cells or partition rects of the spatial index on the last tick.
+    pub fn partition(&self) -> &[Bounds] {
+        &self.partition
+    }
+    /// Offsets to every neighbour of the entity within the radius of the last tick.
+    pub fn neighbour_offsets(&self, entity: Entity) -> Vec<DVec2> {
+        let pos = match self.position(entity) {
+            Some(pos) => pos.vec,
+            None => return vec![],
+        };
+        let radius = self.stats.neighbour_radius;
+        let mut offsets = vec![];
+        let push = |other: Neighbour| offsets.push(other.diff);
+        match self.config.physics.spatial_index {
+            SpatialIndex::Grid => self.grid.for_each_neighbour(pos, radius, push),
+            SpatialIndex::Partition => {
+                let bump = Bump::new();
+                Space::build(&self.world, &bump).for_each_neighbour(pos, radius, push);
+            }
+        }
+        offsets
+    }
+    /// Dot which is closest to `pos`.
+    pub fn nearest(&self, pos: DVec2) -> Option<Entity> {
+        self.world
+            .query::<&Position>()
+            .with::<Color>()
+            .iter()
+            .map(|(entity, other)| (entity, FloatOrd(other.vec.distance_squared(pos))))
+            .min_by_key(|(_, dist)| *dist)
+            .map(|(entity, _)| entity)
     }
 
     pub fn player_input(&self, player: Entity, dir: DVec2) -> Option<()> {
@@ -363,68 +790,182 @@ impl State {
             .ok()?
             .with::<Player>();
         let speed = query.get()?;
-        speed.vec += dir * 0.01;
+        speed.vec += dir * self.config.player.input_impulse;
+        Some(())
+    }
+    /// Whether `player` holds the ability button, until told otherwise.
+    pub fn player_ability(&self, player: Entity, held: bool) -> Option<()> {
+        self.world.get_mut::<Player>(player).ok()?.ability = held;
         Some(())
     }
     pub fn position(&self, entity: Entity) -> Option<Position> {
         self.world.get::<Position>(entity).ok().as_deref().copied()
     }
+    /// Position between the previous and the current tick, `alpha` is in `0.0..=1.0`.
+    pub fn interpolated_position(&self, entity: Entity, alpha: f64) -> Option<Position> {
+        let pos = self.position(entity)?;
+        let last_pos = self.world.get::<LastPosition>(entity).ok()?.0;
+        Some(last_pos.lerp(pos, alpha))
+    }
     pub fn size(&self, entity: Entity) -> Option<Size> {
         self.world.get::<Size>(entity).ok().as_deref().copied()
     }
 
+    /// Number of dots not owned by any player.
+    pub fn free_dots(&self) -> usize {
+        self.world
+            .query::<&Color>()
+            .without::<Player>()
+            .without::<IsPlayer>()
+            .iter()
+            .count()
+    }
+    /// `None` once the core is eliminated.
+    pub fn player_stats(&self, player: Entity) -> Option<PlayerStats> {
+        let mut query = self.world.query_one::<(&Player, &Size)>(player).ok()?;
+        let (player, size) = query.get()?;
+        let energy_size = player.energy_size();
+        Some(PlayerStats {
+            energy: player.energy,
+            energy_size,
+            size: size.0,
+            orbiting_dots: player.dots.len(),
+            blackhole: self.config.player.is_blackhole(energy_size),
+        })
+    }
+    /// Hue of every orbiting dot of the core, empty once it is eliminated.
+    pub fn orbiting_hues(&self, player: Entity) -> Vec<f64> {
+        match self.world.get::<Player>(player) {
+            Ok(player) => player
+                .dots
+                .iter()
+                .filter_map(|&dot| Some(self.world.get::<Color>(dot).ok()?.hsl.hue()))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
     pub fn batch_spawn_dots(&mut self, n: usize) {
-        let mut random = Random::default();
+        self.spawn_dots_within(n, 10.0);
+    }
+    fn spawn_dots_within(&mut self, n: usize, spread: f64) {
+        let random = &mut self.random;
 
         let to_spawn = (0..n).map(|_| {
             let pos = Position {
-                vec: random.dvec2(10.0),
-            };
-            let speed = Speed {
-                vec: pos.vec,
+                vec: random.dvec2(spread),
             };
+            let last_pos = LastPosition(pos);
+            let speed = Speed { vec: pos.vec };
             let color = random.color();
-            let neighbours = Neighbours::default();
 
-            (pos, speed, color, neighbours)
+            (pos, last_pos, speed, color)
         });
 
         self.world.spawn_batch(to_spawn);
     }
 
-    pub fn respawn_player(&mut self, player: Entity) {
-        let pos = Position::default();
+    pub fn respawn_player(&mut self, player: Entity, pos: DVec2) {
+        let pos = Position { vec: pos };
+        let last_pos = LastPosition(pos);
         let speed = Speed::default();
         let size = Size(1.0);
         let player_component = Player::new();
         let rgb = Rgb::new(255.0, 255.0, 255.0, None);
         let color = Color { hsl: rgb.into() };
-        self.world
-            .spawn_at(player, (player_component, pos, speed, size, color));
+        let bot = self.world.get::<Bot>(player).ok().map(|bot| *bot);
+        self.grid.unindex(&self.world, player);
+        self.world.spawn_at(
+            player,
+            (player_component, pos, last_pos, speed, size, color),
+        );
+        if let Some(bot) = bot {
+            let _ = self.world.insert_one(player, bot);
+        }
 
         Player::add_dot(&mut self.world, player);
     }
 
-    pub fn spawn_player(&mut self) -> Entity {
+    pub fn spawn_player(&mut self, pos: DVec2) -> Entity {
         let player = self.world.reserve_entity();
-        self.respawn_player(player);
+        self.respawn_player(player, pos);
         player
     }
 
-    pub fn dots(&self) -> BumpVec<'_, Dot> {
-        let mut query = self.world.query::<(&Color, &Position, Option<&Size>, Option<&IsPlayer>, Option<&Player>)>();
-        let iter = query.iter().map(|(_, (color, &pos, size, is_player, player))| Dot {
-            color: color.clone(),
-            pos,
-            size: size.unwrap_or(&Size(1.0)).0.powf(0.5) as f32,
-            hollow: is_player.is_some() || player.is_some(),
-        });
+    /// Core driven by the AI instead of `player_input`.
+    pub fn spawn_bot(&mut self, pos: DVec2, difficulty: Difficulty) -> Entity {
+        let bot = self.spawn_player(pos);
+        let _ = self.world.insert_one(bot, Bot::new(difficulty, bot.id()));
+        bot
+    }
+    /// AI cores which are still in the game, in the order they were spawned.
+    pub fn bots(&self) -> Vec<(Entity, Difficulty)> {
+        let mut bots: Vec<_> = self
+            .world
+            .query::<&Bot>()
+            .iter()
+            .map(|(entity, bot)| (entity, bot.difficulty))
+            .collect();
+        bots.sort_by_key(|(entity, _)| *entity);
+        bots
+    }
+
+    /// Despawns the core with its orbiting dots, free dots are left as they are.
+    pub fn remove_player(&mut self, player: Entity) {
+        let dots = match self.world.get::<Player>(player) {
+            Ok(player) => player.dots.clone(),
+            Err(_) => return,
+        };
+        for dot in dots {
+            self.grid.despawn(&mut self.world, dot);
+        }
+        self.grid.despawn(&mut self.world, player);
+    }
+
+    /// Dots to draw, positions are interpolated with `alpha` like in `interpolated_position`.
+    pub fn dots(&self, alpha: f64) -> BumpVec<'_, Dot> {
+        let mut query = self.world.query::<(
+            &Color,
+            &Position,
+            Option<&LastPosition>,
+            Option<&Size>,
+            Option<&IsPlayer>,
+            Option<&Player>,
+        )>();
+        let iter = query.iter().map(
+            |(entity, (color, &pos, last_pos, size, is_player, player))| Dot {
+                entity,
+                color: color.clone(),
+                pos: last_pos.map_or(pos, |last_pos| last_pos.0.lerp(pos, alpha)),
+                size: size.unwrap_or(&Size(1.0)).0.powf(0.5) as f32,
+                hollow: is_player.is_some() || player.is_some(),
+            },
+        );
         BumpVec::from_iter_in(iter, &self.bump)
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TickStats {
+    pub tick_time: Duration,
+    pub neighbours_time: Duration,
+    pub neighbour_radius: f64,
+    /// Number of grid cells or partition rects in the spatial index.
+    pub cells: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerStats {
+    pub energy: f64,
+    pub energy_size: f64,
+    pub size: f64,
+    pub orbiting_dots: usize,
+    pub blackhole: bool,
+}
+
 #[derive(Debug)]
 pub struct Dot {
+    pub entity: Entity,
     pub color: Color,
     pub pos: Position,
     pub size: f32,
@@ -0,0 +1,193 @@
+use super::{Bot, Player, Score, State};
+use float_ord::FloatOrd;
+use hecs::Entity;
+use std::cmp::Reverse;
+
+/// How a round is won or lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameMode {
+    /// Endless, the world starts over once it runs out of free dots.
+    #[default]
+    Zen,
+    /// Most energy when the time is up or the world is drained wins.
+    TimeAttack,
+    /// Free dots don't come back and cores burn energy, a core which shrinks too much is out.
+    /// Last core left wins, a lone core wins by draining the whole world.
+    Survival,
+    /// First core to collapse into a blackhole wins.
+    BlackholeRace,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Zen,
+        GameMode::TimeAttack,
+        GameMode::Survival,
+        GameMode::BlackholeRace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Zen => "Zen",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Survival => "Survival",
+            GameMode::BlackholeRace => "Blackhole Race",
+        }
+    }
+}
+
+/// How a core did in the round, kept after it is eliminated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub core: Entity,
+    pub bot: bool,
+    /// Last known energy.
+    pub energy: f64,
+    /// Round tick the core became a blackhole at.
+    pub blackhole_at: Option<u32>,
+    /// Round tick the core was eliminated at.
+    pub out_at: Option<u32>,
+    pub score: Score,
+}
+
+/// Final standings of a finished round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Results {
+    pub mode: GameMode,
+    /// Length of the round.
+    pub ticks: u32,
+    /// `None` when nobody made it.
+    pub winner: Option<Entity>,
+    /// Every core of the round, from the winner down.
+    pub standings: Vec<Standing>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Round {
+    pub mode: GameMode,
+    pub ticks: u32,
+    pub standings: Vec<Standing>,
+    pub results: Option<Results>,
+    /// Times Zen started the world over.
+    pub restarts: u32,
+    /// How the world went before the last restart.
+    pub drained: Option<Results>,
+}
+
+impl Round {
+    /// Zen starts over with a fresh clock, the world that ended is kept as a summary.
+    pub fn restart(&mut self) {
+        let standings = std::mem::take(&mut self.standings);
+        self.drained = Some(results(self.mode, self.ticks, standings));
+        self.restarts += 1;
+        self.ticks = 0;
+    }
+}
+
+/// Applies the rules of the mode after a tick and ends the round once it is decided.
+pub(super) fn update(state: &mut State) {
+    let mode = state.round.mode;
+    if mode == GameMode::Survival {
+        let config = &state.config.round;
+        let mut out = vec![];
+        for (core, player) in state.world.query_mut::<&mut Player>() {
+            player.energy = (player.energy - config.survival_upkeep).max(0.0);
+            if player.energy_size() < config.survival_min_size {
+                out.push(core);
+            }
+        }
+        for core in out {
+            state.remove_player(core);
+        }
+    }
+
+    let drained =
+        matches!(mode, GameMode::TimeAttack | GameMode::Survival) && state.free_dots() == 0;
+    let round = &mut state.round;
+    round.ticks += 1;
+    let ticks = round.ticks;
+    let mut alive = vec![];
+    for (core, (player, bot)) in state.world.query_mut::<(&mut Player, Option<&Bot>)>() {
+        alive.push(core);
+        let standing = match round.standings.iter().position(|s| s.core == core) {
+            Some(i) => &mut round.standings[i],
+            None => {
+                round.standings.push(Standing {
+                    core,
+                    bot: bot.is_some(),
+                    energy: 0.0,
+                    blackhole_at: None,
+                    out_at: None,
+                    score: Score::default(),
+                });
+                round.standings.last_mut().unwrap()
+            }
+        };
+        standing.energy = player.energy;
+        let energy_size = player.energy_size();
+        if state.config.player.is_blackhole(energy_size) {
+            standing.blackhole_at.get_or_insert(ticks);
+        }
+        let score = &mut standing.score;
+        score.add(std::mem::take(&mut player.drain));
+        score.peak_size = score.peak_size.max(energy_size);
+        // Orbiting dots stripped by a blackhole still count as reached.
+        while score.milestones.len() < player.dots.len() {
+            score.milestones.push(ticks);
+        }
+    }
+    for standing in &mut round.standings {
+        if standing.out_at.is_none() && !alive.contains(&standing.core) {
+            standing.out_at = Some(ticks);
+        }
+    }
+
+    let standings = &round.standings;
+    let left = standings.iter().filter(|s| s.out_at.is_none()).count();
+    let players_out =
+        standings.iter().any(|s| !s.bot) && standings.iter().all(|s| s.bot || s.out_at.is_some());
+    let finished = match mode {
+        GameMode::Zen => false,
+        GameMode::TimeAttack => {
+            players_out || drained || ticks >= state.config.round.time_attack_ticks
+        }
+        GameMode::Survival => players_out || (standings.len() > 1 && left <= 1) || drained,
+        GameMode::BlackholeRace => {
+            players_out || standings.iter().any(|s| s.blackhole_at.is_some())
+        }
+    };
+    if finished {
+        round.results = Some(results(mode, ticks, standings.clone()));
+    }
+}
+
+pub(super) fn results(mode: GameMode, ticks: u32, mut standings: Vec<Standing>) -> Results {
+    let winner = match mode {
+        GameMode::BlackholeRace => standings
+            .iter()
+            .filter_map(|s| Some((s.blackhole_at?, Reverse(FloatOrd(s.energy)), s.core)))
+            .min()
+            .map(|(_, _, core)| core),
+        _ => standings
+            .iter()
+            .filter(|s| s.out_at.is_none())
+            .max_by_key(|s| FloatOrd(s.energy))
+            .map(|s| s.core),
+    };
+    let race = mode == GameMode::BlackholeRace;
+    standings.sort_by_key(|s| {
+        (
+            Some(s.core) != winner,
+            s.blackhole_at.filter(|_| race).unwrap_or(u32::MAX),
+            Reverse(s.out_at.unwrap_or(u32::MAX)),
+            Reverse(FloatOrd(s.energy)),
+        )
+    });
+    Results {
+        mode,
+        ticks,
+        winner,
+        standings,
+    }
+}
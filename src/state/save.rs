@@ -0,0 +1,281 @@
+use super::round;
+use super::{
+    Bot, Color, Drain, GameConfig, GameMode, Grid, IsPlayer, LastPosition, Player, Position,
+    Random, Round, Score, Size, Speed, Standing, State,
+};
+use bumpalo::Bump;
+use colorsys::Hsl;
+use hecs::{Entity, EntityBuilder, World};
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Entity id as written in a save, means nothing outside of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SavedEntity(u64);
+impl From<Entity> for SavedEntity {
+    fn from(entity: Entity) -> Self {
+        SavedEntity(entity.to_bits())
+    }
+}
+
+/// Saved entity ids to the entities spawned on load.
+#[derive(Debug, Default)]
+pub struct Remap(HashMap<SavedEntity, Entity>);
+impl Remap {
+    pub fn get(&self, saved: SavedEntity) -> Option<Entity> {
+        self.0.get(&saved).copied()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedColor {
+    hue: f64,
+    saturation: f64,
+    lightness: f64,
+}
+impl From<Color> for SavedColor {
+    fn from(color: Color) -> Self {
+        Self {
+            hue: color.hsl.hue(),
+            saturation: color.hsl.saturation(),
+            lightness: color.hsl.lightness(),
+        }
+    }
+}
+impl From<SavedColor> for Color {
+    fn from(saved: SavedColor) -> Self {
+        Color {
+            hsl: Hsl::new(saved.hue, saved.saturation, saved.lightness, None),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedPlayer {
+    dots: Vec<SavedEntity>,
+    energy: f64,
+    rot: f64,
+    #[serde(default)]
+    immunity: u32,
+    #[serde(default)]
+    ability: bool,
+    /// Drained on the last tick, not in the score yet.
+    #[serde(default)]
+    drain: Drain,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SavedComponents {
+    id: Option<SavedEntity>,
+    position: Option<Position>,
+    last_position: Option<LastPosition>,
+    speed: Option<Speed>,
+    size: Option<Size>,
+    color: Option<Color>,
+    player: Option<SavedPlayer>,
+    is_player: Option<SavedEntity>,
+    bot: Option<Bot>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedStanding {
+    core: SavedEntity,
+    bot: bool,
+    energy: f64,
+    blackhole_at: Option<u32>,
+    out_at: Option<u32>,
+    #[serde(default)]
+    score: Score,
+}
+
+/// Results of a finished round are made again from the standings on load.
+#[derive(Default, Serialize, Deserialize)]
+struct SavedRound {
+    mode: GameMode,
+    ticks: u32,
+    standings: Vec<SavedStanding>,
+    finished: bool,
+    #[serde(default)]
+    restarts: u32,
+}
+
+/// Whole world with the RNG, so a loaded game continues exactly as the saved one would.
+#[derive(Serialize, Deserialize)]
+pub struct SavedState {
+    seed: u64,
+    rng: Pcg64Mcg,
+    #[serde(default)]
+    config: GameConfig,
+    entities: Vec<SavedComponents>,
+    #[serde(default)]
+    round: SavedRound,
+}
+
+impl State {
+    pub fn save(&self) -> SavedState {
+        let entities = self
+            .world
+            .iter()
+            .map(|entity| SavedComponents {
+                id: Some(entity.entity().into()),
+                position: entity.get::<Position>().map(|c| *c),
+                last_position: entity.get::<LastPosition>().map(|c| *c),
+                speed: entity.get::<Speed>().map(|c| *c),
+                size: entity.get::<Size>().map(|c| *c),
+                color: entity.get::<Color>().map(|c| (*c).clone()),
+                player: entity.get::<Player>().map(|player| SavedPlayer {
+                    dots: player.dots.iter().map(|&dot| dot.into()).collect(),
+                    energy: player.energy,
+                    rot: player.rot,
+                    immunity: player.immunity,
+                    ability: player.ability,
+                    drain: player.drain.clone(),
+                }),
+                is_player: entity.get::<IsPlayer>().map(|c| c.0.into()),
+                bot: entity.get::<Bot>().map(|c| *c),
+            })
+            .collect();
+        SavedState {
+            seed: self.seed,
+            rng: self.random.rng.clone(),
+            config: self.config.clone(),
+            entities,
+            round: SavedRound {
+                mode: self.round.mode,
+                ticks: self.round.ticks,
+                standings: self
+                    .round
+                    .standings
+                    .iter()
+                    .map(|standing| SavedStanding {
+                        core: standing.core.into(),
+                        bot: standing.bot,
+                        energy: standing.energy,
+                        blackhole_at: standing.blackhole_at,
+                        out_at: standing.out_at,
+                        score: standing.score.clone(),
+                    })
+                    .collect(),
+                finished: self.round.results.is_some(),
+                restarts: self.round.restarts,
+            },
+        }
+    }
+
+    /// Entities get new ids on load, references between them are remapped.
+    /// Eliminated cores are gone from the world, their standings get ids no entity has.
+    pub fn load(saved: SavedState) -> (Self, Remap) {
+        let mut world = World::new();
+        let mut remap = Remap::default();
+        let spawned: Vec<_> = saved
+            .entities
+            .iter()
+            .map(|saved| {
+                let entity = world.reserve_entity();
+                if let Some(id) = saved.id {
+                    remap.0.insert(id, entity);
+                }
+                entity
+            })
+            .collect();
+
+        let mut builder = EntityBuilder::new();
+        for (saved, entity) in saved.entities.into_iter().zip(spawned) {
+            let SavedComponents {
+                id: _,
+                position,
+                last_position,
+                speed,
+                size,
+                color,
+                player,
+                is_player,
+                bot,
+            } = saved;
+            if let Some(position) = position {
+                builder.add(position);
+            }
+            if let Some(last_position) = last_position {
+                builder.add(last_position);
+            }
+            if let Some(speed) = speed {
+                builder.add(speed);
+            }
+            if let Some(size) = size {
+                builder.add(size);
+            }
+            if let Some(color) = color {
+                builder.add(color);
+            }
+            if let Some(player) = player {
+                builder.add(Player {
+                    dots: player
+                        .dots
+                        .into_iter()
+                        .filter_map(|dot| remap.get(dot))
+                        .collect(),
+                    energy: player.energy,
+                    rot: player.rot,
+                    immunity: player.immunity,
+                    ability: player.ability,
+                    drain: player.drain,
+                });
+            }
+            if let Some(player) = is_player.and_then(|player| remap.get(player)) {
+                builder.add(IsPlayer(player));
+            }
+            if let Some(bot) = bot {
+                builder.add(bot);
+            }
+            world.spawn_at(entity, builder.build());
+        }
+
+        let SavedRound {
+            mode,
+            ticks,
+            standings,
+            finished,
+            restarts,
+        } = saved.round;
+        let standings: Vec<_> = standings
+            .into_iter()
+            .map(|standing| {
+                let core = remap.get(standing.core).unwrap_or_else(|| {
+                    let tombstone = world.spawn(());
+                    let _ = world.despawn(tombstone);
+                    remap.0.insert(standing.core, tombstone);
+                    tombstone
+                });
+                Standing {
+                    core,
+                    bot: standing.bot,
+                    energy: standing.energy,
+                    blackhole_at: standing.blackhole_at,
+                    out_at: standing.out_at,
+                    score: standing.score,
+                }
+            })
+            .collect();
+        let round = Round {
+            mode,
+            ticks,
+            results: finished.then(|| round::results(mode, ticks, standings.clone())),
+            standings,
+            restarts,
+            drained: None,
+        };
+        let state = State {
+            world,
+            bump: Bump::default(),
+            random: Random { rng: saved.rng },
+            seed: saved.seed,
+            config: saved.config,
+            stats: Default::default(),
+            grid: Grid::default(),
+            partition: Vec::new(),
+            round,
+        };
+        (state, remap)
+    }
+}
@@ -0,0 +1,48 @@
+/// Hue circle is split into this many slices for `Score::hues`.
+pub const HUES: usize = 12;
+
+/// How a core did in the round, besides its energy.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Score {
+    /// Energy drained from free dots, Survival upkeep isn't taken off.
+    pub energy_gained: f64,
+    /// Free dots drained until they faded away.
+    pub dots_drained: u32,
+    /// Largest energy size.
+    pub peak_size: f64,
+    /// Round tick the core got each of its orbiting dots at, the first one comes with the core.
+    pub milestones: Vec<u32>,
+    /// Energy drained by hue of the free dot, slice `i` starts at `i * 360 / HUES` degrees.
+    pub hues: [f64; HUES],
+}
+
+impl Score {
+    pub(super) fn add(&mut self, drain: Drain) {
+        self.energy_gained += drain.energy;
+        self.dots_drained += drain.dots;
+        for (total, energy) in self.hues.iter_mut().zip(drain.hues) {
+            *total += energy;
+        }
+    }
+}
+
+/// Drained by a core since the round last took it into the score.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Drain {
+    pub energy: f64,
+    pub dots: u32,
+    pub hues: [f64; HUES],
+}
+
+impl Drain {
+    pub fn add(&mut self, hue: f64, energy: f64) {
+        let slice = (hue.rem_euclid(360.0) / 360.0 * HUES as f64) as usize;
+        self.hues[slice.min(HUES - 1)] += energy;
+    }
+}
@@ -1,8 +1,8 @@
-use super::{Position, State};
+use super::Position;
 use bumpalo::{collections::Vec as BumpVec, Bump};
 use float_ord::FloatOrd;
 use glam::DVec2;
-use hecs::Entity;
+use hecs::{Entity, World};
 
 #[derive(Debug, Clone, Copy)]
 struct Element {
@@ -101,10 +101,12 @@ struct Partition<'a> {
 }
 impl<'a> Partition<'a> {
     fn from_iter_in(iter: impl Iterator<Item = Element>, bump: &'a Bump) -> Self {
-        let mut fields = BumpVec::new_in(&bump);
+        let mut fields = BumpVec::new_in(bump);
         let mut field = Field::new(bump);
         field.extend(iter);
-        fields.push(field);
+        if !field.elements.is_empty() {
+            fields.push(field);
+        }
         Self { fields, bump }
     }
     fn partition_all_once<'b: 'a>(
@@ -115,6 +117,8 @@ impl<'a> Partition<'a> {
     ) -> bool {
         let done = self.fields.iter_mut().any(|field| {
             if field.elements.len() > max_elements {
+                // Elements which all share one position can't be split any further.
+                let before = partitioned.len();
                 let mean = field.get_mean_or_compute();
                 let mut ab = field.split(bump, false, mean.y);
                 if !ab.elements.is_empty() {
@@ -131,7 +135,7 @@ impl<'a> Partition<'a> {
                 if !d.elements.is_empty() {
                     partitioned.push(d);
                 }
-                true
+                partitioned.len() > before
             } else {
                 false
             }
@@ -140,9 +144,9 @@ impl<'a> Partition<'a> {
         done
     }
     fn partition(mut self, max_elements: usize) -> Space<'a> {
-        let mut partitioned = BumpVec::new_in(&self.bump);
-        while self.partition_all_once(&self.bump, &mut partitioned, max_elements) {}
-        let mut rects = BumpVec::with_capacity_in(self.fields.len(), &self.bump);
+        let mut partitioned = BumpVec::new_in(self.bump);
+        while self.partition_all_once(self.bump, &mut partitioned, max_elements) {}
+        let mut rects = BumpVec::with_capacity_in(self.fields.len(), self.bump);
         rects.extend(self.fields.drain(..).map(|field| field.rect()));
         Space { rects }
     }
@@ -154,12 +158,16 @@ struct Rect<'a> {
     elements: BumpVec<'a, Element>,
 }
 impl<'a> Rect<'a> {
+    fn bounds(&self) -> Bounds {
+        Bounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
     fn distance(&self, pos: Position) -> f64 {
-        let x = pos.vec.x.clamp(self.min.x, self.max.x);
-        let y = pos.vec.y.clamp(self.min.y, self.max.y);
-        DVec2::new(x, y).distance(pos.vec)
+        self.bounds().distance(pos.vec)
     }
-    fn neighbours(&'a self, pos: Position, dist: f64) -> impl 'a + Iterator<Item = Neighbour> {
+    fn neighbours(&self, pos: Position, dist: f64) -> impl '_ + Iterator<Item = Neighbour> {
         self.elements
             .iter()
             .map(move |el| {
@@ -175,16 +183,57 @@ impl<'a> Rect<'a> {
     }
 }
 
-struct Space<'a> {
+/// Partition of all entities built from scratch with a bump allocator.
+pub(super) struct Space<'a> {
     rects: BumpVec<'a, Rect<'a>>,
 }
 impl<'a> Space<'a> {
-    fn neighbours(&'a self, pos: Position, dist: f64) -> impl 'a + Iterator<Item = Neighbour> {
+    pub fn build(world: &World, bump: &'a Bump) -> Self {
+        let mut query = world.query::<&Position>();
+        let iter = query.iter().map(|(entity, &pos)| Element { entity, pos });
+        Partition::from_iter_in(iter, bump).partition(32)
+    }
+    fn neighbours(&self, pos: Position, dist: f64) -> impl '_ + Iterator<Item = Neighbour> {
         self.rects
             .iter()
             .filter(move |rect| rect.distance(pos) <= dist)
             .flat_map(move |rect| rect.neighbours(pos, dist))
     }
+    pub fn bounds(&self) -> impl '_ + Iterator<Item = Bounds> {
+        self.rects.iter().map(|rect| rect.bounds())
+    }
+}
+impl<'a> NeighbourQuery for Space<'a> {
+    fn for_each_neighbour(&self, pos: DVec2, dist: f64, f: impl FnMut(Neighbour)) {
+        self.neighbours(Position { vec: pos }, dist).for_each(f);
+    }
+}
+
+/// Radius search over an index of entity positions.
+/// Every entity within `dist` is reported, including the one at `pos` itself.
+pub(super) trait NeighbourQuery {
+    fn for_each_neighbour(&self, pos: DVec2, dist: f64, f: impl FnMut(Neighbour));
+    /// Runs `f` for every item, indexes which can be shared between threads do it in parallel.
+    /// Output is in the order of `items` either way.
+    fn map_queries<T: Sync, R: Send>(
+        &self,
+        items: &[T],
+        f: impl Fn(&Self, &T) -> R + Sync,
+    ) -> Vec<R> {
+        items.iter().map(|item| f(self, item)).collect()
+    }
+}
+
+/// Bounding box of a partition rect or grid cell, kept after the tick for the debug overlay.
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+    pub min: DVec2,
+    pub max: DVec2,
+}
+impl Bounds {
+    pub fn distance(&self, pos: DVec2) -> f64 {
+        pos.clamp(self.min, self.max).distance(pos)
+    }
 }
 
 #[derive(Debug)]
@@ -193,29 +242,3 @@ pub struct Neighbour {
     pub diff: DVec2,
     pub dist: f64,
 }
-
-#[derive(Debug, Default)]
-pub struct Neighbours {
-    pub vec: Vec<Neighbour>,
-}
-
-impl Neighbours {
-    pub fn slice(&self) -> &[Neighbour] {
-        &self.vec
-    }
-    pub fn update(state: &mut State, dist: f64) {
-        {
-            let partition = {
-                let mut query = state.world.query::<&Position>();
-                let iter = query.iter().map(|(entity, &pos)| Element { entity, pos });
-                Partition::from_iter_in(iter, &state.bump)
-            };
-            let space = partition.partition(32);
-            for (_, (pos, neighbours)) in state.world.query_mut::<(&Position, &mut Neighbours)>() {
-                neighbours.vec.clear();
-                neighbours.vec.extend(space.neighbours(*pos, dist));
-            }
-        }
-        state.bump.reset();
-    }
-}
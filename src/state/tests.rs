@@ -0,0 +1,423 @@
+use super::grid::Grid;
+use super::space::{NeighbourQuery, Space};
+use super::*;
+use proptest::prelude::*;
+
+fn point(amp: f64) -> impl Strategy<Value = DVec2> {
+    (-amp..amp, -amp..amp).prop_map(|(x, y)| DVec2::new(x, y))
+}
+
+/// Random point clouds, and degenerate ones where every point is the same.
+fn cloud() -> impl Strategy<Value = Vec<DVec2>> {
+    prop_oneof![
+        prop::collection::vec(point(50.0), 0..300),
+        (point(50.0), 0..300usize).prop_map(|(point, n)| vec![point; n]),
+    ]
+}
+
+fn color() -> impl Strategy<Value = Color> {
+    (0.0..360.0, 0.0..=100.0, 0.0..=100.0).prop_map(|(h, s, l)| Color {
+        hsl: Hsl::new(h, s, l, None),
+    })
+}
+
+fn spawn_cloud(world: &mut World, points: &[DVec2]) -> Vec<Entity> {
+    points
+        .iter()
+        .map(|&vec| world.spawn((Position { vec },)))
+        .collect()
+}
+
+fn brute_force(world: &World, pos: DVec2, dist: f64) -> Vec<Entity> {
+    let mut found: Vec<_> = world
+        .query::<&Position>()
+        .iter()
+        .filter(|(_, other)| (other.vec - pos).length() <= dist)
+        .map(|(entity, _)| entity)
+        .collect();
+    found.sort();
+    found
+}
+
+fn found(index: &impl NeighbourQuery, pos: DVec2, dist: f64) -> Vec<Entity> {
+    let mut found = vec![];
+    index.for_each_neighbour(pos, dist, |other| found.push(other.entity));
+    found.sort();
+    found
+}
+
+proptest! {
+    #[test]
+    fn space_neighbours_match_brute_force(
+        points in cloud(),
+        pos in point(60.0),
+        dist in 0.0..30.0,
+    ) {
+        let mut world = World::new();
+        spawn_cloud(&mut world, &points);
+        let bump = Bump::new();
+        let space = Space::build(&world, &bump);
+        prop_assert_eq!(found(&space, pos, dist), brute_force(&world, pos, dist));
+        for &point in points.iter().take(10) {
+            prop_assert_eq!(found(&space, point, dist), brute_force(&world, point, dist));
+        }
+    }
+
+    #[test]
+    fn grid_neighbours_match_brute_force_after_moves(
+        points in cloud(),
+        moved in prop::collection::vec(point(50.0), 0..300),
+        despawned in prop::collection::vec(any::<bool>(), 0..300),
+        pos in point(60.0),
+        dist in 0.0..30.0,
+        cell_size in 0.5..40.0,
+    ) {
+        let mut world = World::new();
+        let entities = spawn_cloud(&mut world, &points);
+        let mut grid = Grid::default();
+        grid.update(&mut world, cell_size);
+
+        for (&entity, &vec) in entities.iter().zip(&moved) {
+            world.get_mut::<Position>(entity).unwrap().vec = vec;
+        }
+        for (&entity, _) in entities.iter().zip(&despawned).filter(|(_, &despawn)| despawn) {
+            grid.despawn(&mut world, entity);
+        }
+        spawn_cloud(&mut world, moved.get(entities.len()..).unwrap_or_default());
+        grid.update(&mut world, cell_size);
+
+        prop_assert_eq!(found(&grid, pos, dist), brute_force(&world, pos, dist));
+        for &point in moved.iter().take(10) {
+            prop_assert_eq!(found(&grid, point, dist), brute_force(&world, point, dist));
+        }
+    }
+
+    #[test]
+    fn neighbour_attraction_is_finite(
+        color in color(),
+        other_color in color(),
+        diff in prop_oneof![
+            point(1000.0),
+            point(0.000001),
+            Just(DVec2::ZERO),
+        ],
+    ) {
+        let mut world = World::new();
+        let entity = world.spawn((other_color,));
+        let other = Neighbour {
+            entity,
+            diff,
+            dist: diff.length(),
+        };
+        let config = PhysicsConfig::default();
+        if let Some(force) = neighbour_attraction(&color, &Colors::new(&world), &config, &other) {
+            prop_assert!(force.is_finite(), "{:?}", force);
+        }
+    }
+
+    #[test]
+    fn saturation_drain_never_goes_negative(
+        dots in prop::collection::vec((point(2.0), 0.0..=100.0), 1..100),
+        energy in 0.0..1000.0,
+    ) {
+        let mut state = State::with_seed(0);
+        for (vec, saturation) in dots {
+            let color = Color {
+                hsl: Hsl::new(0.0, saturation, 50.0, None),
+            };
+            state.world.spawn((Position { vec }, color));
+        }
+        let player = state.spawn_player(DVec2::ZERO);
+        state.world.get_mut::<Player>(player).unwrap().energy = energy;
+        Player::update(&mut state);
+        state.grid.update(&mut state.world, 10.0);
+
+        for _ in 0..5 {
+            Player::consume_energy(&state.world, &state.config.player, &state.grid);
+        }
+        prop_assert!(state.world.get::<Player>(player).unwrap().energy >= energy);
+        for (_, color) in state
+            .world
+            .query::<&Color>()
+            .without::<Player>()
+            .without::<IsPlayer>()
+            .iter()
+        {
+            prop_assert!(color.hsl.saturation() >= 0.0);
+        }
+    }
+
+    #[test]
+    fn blackhole_strips_smaller_core_until_it_is_eliminated(dots in 1..8usize, zen: bool) {
+        let mut state = State::with_seed(0);
+        let mode = if zen { GameMode::Zen } else { GameMode::TimeAttack };
+        state.set_mode(mode);
+        let victim = state.spawn_player(DVec2::ZERO);
+        for _ in 1..dots {
+            Player::add_dot(&mut state.world, victim);
+        }
+        let blackhole = state.spawn_player(DVec2::ZERO);
+        let energy = (state.config.player.blackhole_size + 1.0).powi(2);
+        state.world.get_mut::<Player>(blackhole).unwrap().energy = energy;
+        Player::update(&mut state);
+
+        let cooldown = state.config.player.strip_cooldown as usize;
+        for _ in 0..dots * (cooldown + 1) {
+            Player::strip(&mut state);
+            Player::update(&mut state);
+        }
+        prop_assert!(state.player_stats(blackhole).is_some());
+        if zen {
+            // Only stripped down to the last orbiting dot.
+            let victim = state.player_stats(victim);
+            prop_assert_eq!(victim.map(|stats| stats.orbiting_dots), Some(1));
+            prop_assert_eq!(state.free_dots(), dots - 1);
+        } else {
+            prop_assert!(state.player_stats(victim).is_none());
+            prop_assert_eq!(state.free_dots(), dots);
+        }
+    }
+
+    #[test]
+    fn ability_stretches_the_reach_of_a_blackhole(distance in 2.0..10.0, ability: bool) {
+        let mut state = State::with_seed(0);
+        let victim = state.spawn_player(DVec2::new(distance, 0.0));
+        Player::add_dot(&mut state.world, victim);
+        let blackhole = state.spawn_player(DVec2::ZERO);
+        let energy = (state.config.player.blackhole_size + 1.0).powi(2);
+        state.world.get_mut::<Player>(blackhole).unwrap().energy = energy;
+        Player::update(&mut state);
+        state.player_ability(blackhole, ability);
+
+        // Nearest orbiting dot of the victim lies between the two reaches.
+        let dots = state.world.get::<Player>(victim).unwrap().dots.clone();
+        let nearest = dots
+            .iter()
+            .map(|&dot| state.world.get::<Position>(dot).unwrap().vec.length())
+            .fold(f64::INFINITY, f64::min);
+        state.config.player.strip_radius = nearest * 0.5;
+        state.config.player.ability_strip_radius = nearest * 2.0;
+        Player::strip(&mut state);
+        let free_dots = if ability { 1 } else { 0 };
+        prop_assert_eq!(state.free_dots(), free_dots);
+    }
+
+    #[test]
+    fn bot_heads_for_a_dot_of_its_color(target in point(40.0), difficulty in 0..3usize) {
+        prop_assume!(target.length() > 1.0);
+        let mut state = State::with_seed(0);
+        let bot = state.spawn_bot(DVec2::ZERO, Difficulty::ALL[difficulty]);
+        let own = state.world.get::<Player>(bot).unwrap().dots[0];
+        let hsl = state.world.get::<Color>(own).unwrap().hsl.clone();
+        let pos = Position { vec: target };
+        state.world.spawn((pos, LastPosition(pos), Speed::default(), Color { hsl }));
+        state.grid.update(&mut state.world, 1.0);
+
+        // Long enough for any difficulty to make up its mind.
+        for _ in 0..=40 {
+            ai::think(&state.world, &state.config, &state.grid);
+        }
+        ai::steer(&mut state.world, &state.config);
+        let dir = state.world.get::<Speed>(bot).unwrap().vec.normalize_or_zero();
+        // Bots weigh a few headings only, the closest one is at most 30 degrees off.
+        prop_assert!(dir.dot(target.normalize()) > 0.86, "heading {} for {}", dir, target);
+    }
+
+    #[test]
+    fn survival_is_lost_by_a_starving_core(upkeep in 0.01..0.5) {
+        let mut state = State::with_seed(0);
+        state.set_mode(GameMode::Survival);
+        state.config.round.survival_upkeep = upkeep;
+        let player = state.spawn_player(DVec2::ZERO);
+        let far = Position { vec: DVec2::new(50.0, 0.0) };
+        let hsl = Hsl::new(0.0, 100.0, 50.0, None);
+        state.world.spawn((far, LastPosition(far), Speed::default(), Color { hsl }));
+
+        for _ in 0..1000 {
+            state.tick();
+        }
+        let results = state.results().expect("round is over");
+        prop_assert_eq!(results.winner, None);
+        prop_assert!(state.player_stats(player).is_none());
+        prop_assert_eq!(results.standings[0].out_at, Some(results.ticks));
+    }
+
+    #[test]
+    #[cfg(feature = "persistence")]
+    fn saved_game_loads_with_eliminated_cores(out in 1..3usize, seed in any::<u64>()) {
+        let mut state = State::with_seed(seed);
+        state.set_mode(GameMode::Survival);
+        state.spawn_dots_within(50, 10.0);
+        let cores: Vec<_> = (0..3).map(|i| state.spawn_player(spawn_point(i, 3))).collect();
+        for _ in 0..5 {
+            state.tick();
+        }
+        for &core in &cores[..out] {
+            state.remove_player(core);
+        }
+        state.tick();
+        prop_assert_eq!(state.results().is_some(), out == 2);
+
+        let ron = ron::ser::to_string(&state.save()).unwrap();
+        let (loaded, remap) = State::load(ron::from_str(&ron).unwrap());
+        prop_assert!(fingerprint(&loaded) == fingerprint(&state));
+        let players = |state: &State| {
+            let mut players: Vec<_> = state
+                .world
+                .query::<&Player>()
+                .iter()
+                .map(|(_, player)| (player.energy.to_bits(), player.dots.len(), player.immunity))
+                .collect();
+            players.sort_unstable();
+            players
+        };
+        prop_assert_eq!(players(&loaded), players(&state));
+        prop_assert_eq!(loaded.round.mode, state.round.mode);
+        prop_assert_eq!(loaded.round.ticks, state.round.ticks);
+        let remapped = |standings: &[Standing]| -> Vec<Standing> {
+            standings
+                .iter()
+                .map(|standing| Standing {
+                    core: remap.get(standing.core.into()).unwrap(),
+                    ..standing.clone()
+                })
+                .collect()
+        };
+        prop_assert_eq!(&loaded.round.standings, &remapped(&state.round.standings));
+        prop_assert_eq!(loaded.round.standings.len(), 3);
+        let results = state.results().map(|results| Results {
+            winner: results.winner.and_then(|core| remap.get(core.into())),
+            standings: remapped(&results.standings),
+            ..results.clone()
+        });
+        prop_assert_eq!(loaded.results(), results.as_ref());
+    }
+
+    #[test]
+    fn added_dots_have_evenly_spaced_hues(added in 0..40usize) {
+        let mut world = World::new();
+        let player = world.spawn((Player::new(),));
+        for _ in 0..=added {
+            Player::add_dot(&mut world, player);
+        }
+        let dots = world.get::<Player>(player).unwrap().dots.clone();
+        prop_assert_eq!(dots.len(), added + 1);
+        let step = 360.0 / dots.len() as f64;
+        for (i, dot) in dots.into_iter().enumerate() {
+            let hue = world.get::<Color>(dot).unwrap().hsl.hue();
+            prop_assert!((hue - step * i as f64).abs() < 1e-6, "dot {} has hue {}", i, hue);
+        }
+    }
+}
+
+// Whole ticks of a populated world are slow in debug builds.
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn score_adds_up_what_the_core_drained(hue in 0.0..20.0) {
+        let mut state = State::with_seed(0);
+        state.set_mode(GameMode::TimeAttack);
+        let player = state.spawn_player(DVec2::ZERO);
+        for i in 0..40 {
+            let angle = i as f64 * 0.7;
+            let vec = DVec2::new(angle.cos(), angle.sin()) * (0.5 + i as f64 / 40.0);
+            let pos = Position { vec };
+            let hsl = Hsl::new(hue, 100.0, 50.0, None);
+            state.world.spawn((pos, LastPosition(pos), Speed::default(), Color { hsl }));
+        }
+
+        for _ in 0..100 {
+            state.tick();
+        }
+        let stats = state.player_stats(player).unwrap();
+        let standing = state.score(player).unwrap();
+        let score = &standing.score;
+        prop_assert!(score.energy_gained > 0.0);
+        // Last tick's drain is taken into the score on the next one.
+        let pending = stats.energy - standing.energy;
+        prop_assert!((1.0 + score.energy_gained + pending - stats.energy).abs() < 1e-9);
+        let slice = (hue / 360.0 * HUES as f64) as usize;
+        prop_assert!((score.hues[slice] - score.energy_gained).abs() < 1e-9);
+        prop_assert!(score.peak_size >= standing.energy.sqrt());
+        prop_assert_eq!(score.milestones.len(), stats.orbiting_dots);
+    }
+}
+
+/// Every dot and core by its components, in no particular order, for comparing whole worlds.
+fn fingerprint(state: &State) -> Vec<[u64; 7]> {
+    let mut dots: Vec<_> = state
+        .world
+        .query::<(&Position, &Speed, &Color)>()
+        .iter()
+        .map(|(_, (pos, speed, color))| {
+            let hsl = &color.hsl;
+            [
+                pos.vec.x,
+                pos.vec.y,
+                speed.vec.x,
+                speed.vec.y,
+                hsl.hue(),
+                hsl.saturation(),
+                hsl.lightness(),
+            ]
+            .map(f64::to_bits)
+        })
+        .collect();
+    dots.sort_unstable();
+    dots
+}
+
+/// Game with a player and a rival after `ticks` ticks of steering in circles.
+fn played(seed: u64, ticks: u32) -> State {
+    let (mut state, players) = State::new_game_with_bots(seed, 1, &[Difficulty::Hard]);
+    for tick in 0..ticks {
+        let angle = tick as f64 * 0.05;
+        state.player_input(players[0], DVec2::new(angle.cos(), angle.sin()));
+        state.tick();
+    }
+    state
+}
+
+// Whole games, a few cases are plenty.
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(4))]
+
+    #[test]
+    fn same_seed_and_inputs_give_the_same_world(seed in any::<u64>()) {
+        let state = played(seed, 60);
+        prop_assert!(fingerprint(&played(seed, 60)) == fingerprint(&state));
+        prop_assert!(fingerprint(&played(seed ^ 1, 60)) != fingerprint(&state));
+    }
+
+    #[test]
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    fn thread_count_does_not_change_the_world(seed in any::<u64>(), threads in 2..8usize) {
+        let on = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| fingerprint(&played(seed, 60)))
+        };
+        prop_assert!(on(threads) == on(1));
+    }
+
+    #[test]
+    #[cfg(feature = "persistence")]
+    fn loaded_game_continues_like_the_saved_one(seed in any::<u64>(), grid: bool) {
+        let (mut state, _) = State::new_game_with_bots(seed, 1, &[Difficulty::Hard]);
+        state.config.physics.spatial_index = if grid {
+            SpatialIndex::Grid
+        } else {
+            SpatialIndex::Partition
+        };
+        for _ in 0..60 {
+            state.tick();
+        }
+        let ron = ron::ser::to_string(&state.save()).unwrap();
+        let (mut loaded, _) = State::load(ron::from_str(&ron).unwrap());
+        for _ in 0..60 {
+            state.tick();
+            loaded.tick();
+        }
+        prop_assert!(fingerprint(&loaded) == fingerprint(&state));
+    }
+}